@@ -0,0 +1,64 @@
+use std::ops::{Index, IndexMut};
+
+use super::state::DEFAULT_BOARD_SIZE;
+
+/// Square grid of per-cell values, indexed by `(row, col)` with row 0 at the bottom.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone + Default> Board<T> {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            cells: vec![T::default(); size * size],
+        }
+    }
+}
+
+impl<T: Clone + Default> Default for Board<T> {
+    fn default() -> Self {
+        Self::new(DEFAULT_BOARD_SIZE)
+    }
+}
+
+impl<T> Board<T> {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Iterates over all cells as `((row, col), value)`.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let size = self.size;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i / size, i % size), cell))
+    }
+
+    /// Converts signed coordinates to a cell, if they fall on the board.
+    pub fn cell(&self, row: isize, col: isize) -> Option<(usize, usize)> {
+        let size = self.size as isize;
+        if (0..size).contains(&row) && (0..size).contains(&col) {
+            Some((row as usize, col as usize))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Board<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        &self.cells[row * self.size + col]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Board<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        &mut self.cells[row * self.size + col]
+    }
+}
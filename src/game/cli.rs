@@ -0,0 +1,66 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::mcts::MctsConfig;
+
+/// Command-line options.
+#[derive(Default, Debug)]
+pub struct CliArgs {
+    /// Run as the reference engine on stdin and stdout instead of opening
+    /// a window (`--engine`).
+    pub engine: bool,
+    /// External engine commands to offer as computer players
+    /// (`--bot "<command> [args]"`, repeatable).
+    pub bots: Vec<String>,
+    /// Budget and tuning of the MCTS computer player (`--mcts-iterations`,
+    /// `--mcts-time <ms>`, `--mcts-exploration`, `--mcts-seed`).
+    pub mcts: MctsConfig,
+    /// Seed of the first game, to play a game again exactly (`--seed`).
+    pub seed: Option<u64>,
+    /// Play this many games per board between neighbouring computer levels
+    /// and print their scores instead of opening a window (`--calibrate`).
+    pub calibrate: Option<usize>,
+}
+
+impl CliArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--engine" => parsed.engine = true,
+                "--bot" => parsed.bots.extend(args.next()),
+                "--mcts-iterations" => {
+                    if let Some(iterations) = parse_value(&arg, args.next()) {
+                        parsed.mcts.iterations = iterations;
+                    }
+                }
+                "--mcts-time" => {
+                    if let Some(millis) = parse_value(&arg, args.next()) {
+                        parsed.mcts.time_budget = Some(Duration::from_millis(millis));
+                    }
+                }
+                "--mcts-exploration" => {
+                    if let Some(exploration) = parse_value(&arg, args.next()) {
+                        parsed.mcts.exploration = exploration;
+                    }
+                }
+                "--mcts-seed" => parsed.mcts.seed = parse_value(&arg, args.next()),
+                "--seed" => parsed.seed = parse_value(&arg, args.next()),
+                "--calibrate" => parsed.calibrate = parse_value(&arg, args.next()),
+                _ => eprintln!("Ignoring unknown argument {arg}"),
+            }
+        }
+        parsed
+    }
+}
+
+/// Parses the number following `flag`, complaining when it is missing or
+/// malformed.
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Option<T> {
+    let parsed = value.as_deref().and_then(|value| value.parse().ok());
+    if parsed.is_none() {
+        eprintln!("Ignoring {flag}: expected a number");
+    }
+    parsed
+}
@@ -0,0 +1,236 @@
+//! Coach for human players: a move that turns a won or drawn position into a
+//! lost one is held back with a warning until the player confirms it or
+//! takes it back.
+
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+
+use super::hint::line_name;
+use super::review::describe_move;
+use super::solver::{self, Outcome, Solver};
+use super::state::{GameMode, GameMove, GameState, Player};
+use super::systems::{find_winning_move, make_move};
+
+/// Distinct positions the coach may solve per click. The check runs while
+/// the click is handled, so it stays small; bigger positions go unchecked.
+const COACH_POSITIONS: usize = 5_000;
+
+/// Boards with more moves open than this are left unchecked without trying.
+const MAX_OPEN_MOVES: usize = 16;
+
+/// A move waiting for the player to confirm it.
+#[derive(Clone, Debug)]
+pub struct HeldMove {
+    pub mv: GameMove,
+    /// Such as "this allows a fork on the bottom-left".
+    pub warning: String,
+}
+
+/// Plays a human's move, or holds it when the coach is on and the move
+/// throws away a win or a draw.
+pub fn play_or_hold(state: &mut GameState, mv: GameMove) {
+    if state.rules.coach {
+        if let Some(warning) = warning(state, mv) {
+            state.held = Some(HeldMove { mv, warning });
+            return;
+        }
+    }
+    make_move(state, mv);
+}
+
+/// Plays the held move after all.
+pub fn confirm(state: &mut GameState) {
+    if let Some(held) = state.held.take() {
+        make_move(state, held.mv);
+    }
+}
+
+/// Why `mv` loses, when the side to move could otherwise win or draw; `None`
+/// for a safe move or a position too big to check.
+fn warning(state: &GameState, mv: GameMove) -> Option<String> {
+    if !solver::supports(state) || state.setup_phase || state.legal_moves().len() > MAX_OPEN_MOVES {
+        return None;
+    }
+    let cancel = AtomicBool::new(false);
+    let mut solver = Solver::with_limit(&cancel, COACH_POSITIONS);
+    if let Outcome::Loss(_) = solver.solve(state)? {
+        return None;
+    }
+    let mut after = state.clone();
+    make_move(&mut after, mv);
+    match solver.solve(&after)?.before_move(after.turn == state.turn) {
+        Outcome::Loss(plies) => Some(explain(&after, plies)),
+        _ => None,
+    }
+}
+
+/// Describes the opponent's best answer: an immediate win, a fork, or just
+/// a forced win some moves away.
+fn explain(after: &GameState, plies: u32) -> String {
+    let opponent = after.turn;
+    let name = opponent.letter();
+    if let Some(reply) = find_winning_move(after, opponent) {
+        let mut won = after.clone();
+        make_move(&mut won, reply);
+        let line = won
+            .winning_line
+            .as_ref()
+            .map_or("line", |line| line_name(&won, line));
+        return format!(
+            "this lets {name} complete a {line} at {}",
+            describe_move(reply)
+        );
+    }
+
+    if let Some(fork) = fork(after, 1) {
+        return format!("this allows a fork on the {}", place(after, fork));
+    }
+    format!(
+        "this lets {name} force a win within {} moves",
+        (plies / 2).max(1)
+    )
+}
+
+/// A move for the side to move leaving two threats the other side can't
+/// both block, possibly after `forcing` threats that must each be blocked.
+fn fork(state: &GameState, forcing: usize) -> Option<GameMove> {
+    let player = state.turn;
+    state.legal_moves().into_iter().find_map(|mv| {
+        let mut after = state.clone();
+        make_move(&mut after, mv);
+        if after.is_over() || find_winning_move(&after, after.turn).is_some() {
+            return None;
+        }
+        match threats(&after, player) {
+            0 => None,
+            1 if forcing > 0 => {
+                let block = find_winning_move(&after, player)?;
+                let mut blocked = after.clone();
+                make_move(&mut blocked, block);
+                if blocked.is_over() || blocked.turn != player {
+                    return None;
+                }
+                fork(&blocked, forcing - 1)
+            }
+            1 => None,
+            _ => Some(mv),
+        }
+    })
+}
+
+/// Distinct cells where `player` would win at once.
+fn threats(state: &GameState, player: Player) -> usize {
+    let mut test_state = state.clone();
+    test_state.turn = player;
+    let cells: HashSet<_> = test_state
+        .legal_moves()
+        .into_iter()
+        .filter(|&mv| {
+            let mut after = test_state.clone();
+            make_move(&mut after, mv);
+            after.winner == Some(player)
+        })
+        .map(|mv| match mv {
+            GameMove::PlaceOn(board, pos) => (board, pos),
+            _ => (0, mv.target()),
+        })
+        .collect();
+    cells.len()
+}
+
+/// Where a move lands in plain words, such as "bottom-left" or "centre of
+/// layer 2".
+fn place(state: &GameState, mv: GameMove) -> String {
+    let size = state.rules.board_size;
+    match mv {
+        GameMove::PlaceOn(index, pos) => {
+            let board = if state.rules.mode == GameMode::Qubic {
+                "layer"
+            } else {
+                "board"
+            };
+            format!("{} of {board} {}", region(pos, size), index + 1)
+        }
+        _ => region(mv.target(), size).to_string(),
+    }
+}
+
+/// The third of the board a cell lies in; row 0 is the bottom.
+fn region((row, col): (usize, usize), size: usize) -> &'static str {
+    let third = |i: usize| (i * 3 / size.max(1)).min(2);
+    match (third(row), third(col)) {
+        (0, 0) => "bottom-left",
+        (0, 1) => "bottom",
+        (0, _) => "bottom-right",
+        (1, 0) => "left",
+        (1, 1) => "centre",
+        (1, _) => "right",
+        (_, 0) => "top-left",
+        (_, 1) => "top",
+        (_, _) => "top-right",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::GameRules;
+
+    fn coached(moves: &[(usize, usize)]) -> GameState {
+        let mut state = GameState {
+            rules: GameRules {
+                coach: true,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        for &pos in moves {
+            make_move(&mut state, GameMove::Place(pos));
+        }
+        state
+    }
+
+    #[test]
+    fn an_edge_reply_to_the_centre_allows_a_fork() {
+        let state = coached(&[(1, 1)]);
+        let text = warning(&state, GameMove::Place((0, 1))).unwrap();
+        assert!(text.contains("fork"), "{text}");
+        assert_eq!(warning(&state, GameMove::Place((0, 0))), None);
+    }
+
+    #[test]
+    fn missing_a_block_names_the_line() {
+        let state = coached(&[(0, 0), (1, 1), (0, 1)]);
+        assert_eq!(
+            warning(&state, GameMove::Place((2, 2))).as_deref(),
+            Some("this lets X complete a row at c1")
+        );
+        assert_eq!(warning(&state, GameMove::Place((0, 2))), None);
+    }
+
+    #[test]
+    fn held_moves_wait_for_confirmation() {
+        let mut state = coached(&[(1, 1)]);
+        play_or_hold(&mut state, GameMove::Place((0, 1)));
+        assert!(state.held.is_some());
+        assert_eq!(state.moves, 1);
+
+        confirm(&mut state);
+        assert!(state.held.is_none());
+        assert_eq!(state.board[(0, 1)], Some(Player::O.symbol()));
+
+        state.rules.coach = false;
+        play_or_hold(&mut state, GameMove::Place((2, 1)));
+        assert!(state.held.is_none());
+        assert_eq!(state.moves, 3);
+    }
+
+    #[test]
+    fn regions() {
+        assert_eq!(region((0, 0), 3), "bottom-left");
+        assert_eq!(region((1, 1), 3), "centre");
+        assert_eq!(region((6, 3), 7), "top");
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
 
+use super::state::Player;
+
 #[derive(Component)]
 pub struct Mark;
 
@@ -9,6 +11,29 @@ pub struct Grid;
 #[derive(Component)]
 pub struct WinHighlight;
 
+/// A mark part dropping into place in gravity mode.
+#[derive(Component)]
+pub struct Falling {
+    pub target_y: f32,
+    pub offset: f32,
+    pub velocity: f32,
+}
+
+/// A hidden mark growing into view when a fog-of-war game ends.
+#[derive(Component)]
+pub struct Revealing {
+    /// Seconds until it starts growing; negative once under way.
+    pub delay: f32,
+}
+
+/// Highlight for a selected mark and its legal destinations.
+#[derive(Component)]
+pub struct MoveHint;
+
+/// Highlight over the hovered column in gravity mode.
+#[derive(Component)]
+pub struct ColumnHover;
+
 #[derive(Component)]
 pub struct MenuUI;
 
@@ -18,18 +43,138 @@ pub struct ScoreboardUI;
 #[derive(Component)]
 pub struct ScoreText;
 
+#[derive(Component)]
+pub struct ThinkingText;
+
+#[derive(Component)]
+pub struct HintButton;
+
+#[derive(Component)]
+pub struct HintButtonText;
+
+#[derive(Component)]
+pub struct HintText;
+
+#[derive(Component)]
+pub struct AnalysisText;
+
+/// The seed of the game in play, to replay it with `--seed`.
+#[derive(Component)]
+pub struct SeedText;
+
+/// Colour and value label on a cell of the analysis overlay.
+#[derive(Component)]
+pub struct AnalysisMarker;
+
+#[derive(Component)]
+pub struct AnalyseButton;
+
+#[derive(Component)]
+pub struct ReviewPanel;
+
+/// Highlight on the move being reviewed, coloured by its grade.
+#[derive(Component)]
+pub struct ReviewMarker;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReviewButton {
+    Previous,
+    Next,
+    Close,
+}
+
+/// The coach's warning with its confirm and take back buttons.
+#[derive(Component)]
+pub struct CoachPanel;
+
+/// Highlight on the cells of the held move.
+#[derive(Component)]
+pub struct CoachMarker;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CoachButton {
+    Confirm,
+    TakeBack,
+}
+
+/// Highlight on the cells of the suggested move.
+#[derive(Component)]
+pub struct HintMarker;
+
 #[derive(Component)]
 pub struct GameOverUI;
 
+/// Cycles who plays a side: a human or one of the registered strategies.
+#[derive(Component)]
+pub struct PlayerSelectButton(pub Player);
+
+#[derive(Component)]
+pub struct PlayerSelectText(pub Player);
+
+/// The - and + buttons under a player playing a computer level; hidden for
+/// other player types.
+#[derive(Component)]
+pub struct DifficultyControl(pub Player);
+
+/// Lowers (-1) or raises (+1) the level of a computer player.
 #[derive(Component)]
-pub struct MenuButton {
-    pub x_type: PlayerType,
-    pub o_type: PlayerType,
+pub struct DifficultyButton {
+    pub player: Player,
+    pub step: i8,
 }
 
+#[derive(Component)]
+pub struct StartGameButton;
+
+#[derive(Component)]
+pub struct OptionButton(pub MenuOption);
+
+#[derive(Component)]
+pub struct OptionText(pub MenuOption);
+
+#[derive(Component)]
+pub struct NewGameButton;
+
 #[derive(Component)]
 pub struct BackToMenuButton;
 
+/// Chooses the cell a pending quantum cycle collapses into.
+#[derive(Component)]
+pub struct CollapseButton(pub (usize, usize));
+
+#[derive(Component)]
+pub struct CollapsePrompt;
+
+/// Picks the number to place next in Numerical mode.
+#[derive(Component)]
+pub struct NumberButton(pub u8);
+
+#[derive(Component)]
+pub struct NumberPicker;
+
+/// Ends the custom setup phase and starts play.
+#[derive(Component)]
+pub struct StartMatchButton;
+
+#[derive(Component)]
+pub struct SetupPrompt;
+
+/// Why the custom setup can't start yet; empty when it can.
+#[derive(Component)]
+pub struct SetupProblemText;
+
+/// Round status in Simultaneous mode.
+#[derive(Component)]
+pub struct RoundPrompt;
+
+/// "Pass the device" screen between hot-seat turns in fog-of-war games.
+#[derive(Component)]
+pub struct FogCurtain;
+
+/// Lifts the fog-of-war curtain once the next player has the device.
+#[derive(Component)]
+pub struct ReadyButton;
+
 #[derive(Component)]
 pub struct Logo;
 
@@ -42,16 +187,32 @@ pub struct LogoAnimation {
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum PlayerType {
     Human,
-    ComputerEasy,
-    ComputerHard,
+    /// A computer using the strategy registered under this name.
+    Computer(&'static str),
 }
 
 impl PlayerType {
-    pub fn label(&self) -> &str {
+    pub fn label(&self) -> String {
         match self {
-            PlayerType::Human => "Human",
-            PlayerType::ComputerEasy => "Computer Easy",
-            PlayerType::ComputerHard => "Computer Hard",
+            PlayerType::Human => "Human".to_string(),
+            PlayerType::Computer(name) => format!("Computer {}", name),
         }
     }
 }
+
+/// Match settings that can be cycled from the menu.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuOption {
+    Mode,
+    BoardSize,
+    WinLength,
+    Gravity,
+    Hex,
+    Wrap,
+    Setup,
+    BoardCount,
+    Players,
+    Fog,
+    Hints,
+    Coach,
+}
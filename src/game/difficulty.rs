@@ -0,0 +1,338 @@
+//! Computer levels 1 to 10. Each level plays the best move it knows with a
+//! set probability and otherwise makes a believable mistake: a weaker move
+//! that doesn't hand the opponent the game on the spot, likelier the better
+//! it looks. `run_calibration` plays the levels against each other to check
+//! each one beats the last.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::sync::atomic::AtomicBool;
+
+use super::qubic;
+use super::solver::{self, Outcome, Solver};
+use super::state::{GameMode, GameMove, GameRules, GameState, Player};
+use super::strategy::{MoveStrategy, StrategyMove, StrategyRegistry};
+use super::systems::{find_hard_move, gives_opponent_win, make_move};
+
+pub const LEVELS: u8 = 10;
+
+/// Level picked when a computer level is first chosen in the menu.
+pub const DEFAULT_LEVEL: u8 = 5;
+
+const LEVEL_NAMES: [&str; LEVELS as usize] = [
+    "Level 1", "Level 2", "Level 3", "Level 4", "Level 5", "Level 6", "Level 7", "Level 8",
+    "Level 9", "Level 10",
+];
+
+/// Chance of playing the best move at each level, calibrated with
+/// `--calibrate` so every level scores clearly above half against the one
+/// below it; the last run is recorded in `doc/CALIBRATION.md`.
+pub const ACCURACY: [f32; LEVELS as usize] =
+    [0.0, 0.2, 0.34, 0.46, 0.56, 0.64, 0.72, 0.79, 0.88, 1.0];
+
+/// Positions solved exactly when there are at most this many moves open;
+/// bigger ones fall back to the Hard heuristics.
+const MAX_SOLVED_MOVES: usize = 9;
+const SOLVER_POSITIONS: usize = 20_000;
+
+/// Boards the calibration games are played on: (size, win length).
+const CALIBRATION_BOARDS: [(usize, usize); 3] = [(3, 3), (5, 4), (6, 4)];
+
+pub struct LevelStrategy {
+    pub level: u8,
+    /// Chance of playing the best move rather than a mistake.
+    pub accuracy: f32,
+}
+
+impl LevelStrategy {
+    pub fn new(level: u8) -> Self {
+        Self {
+            level,
+            accuracy: ACCURACY[usize::from(level - 1)],
+        }
+    }
+}
+
+impl MoveStrategy for LevelStrategy {
+    fn name(&self) -> &'static str {
+        level_name(self.level)
+    }
+
+    fn choose_move(
+        &self,
+        view: &GameState,
+        _side: Player,
+        cancel: &AtomicBool,
+    ) -> Option<StrategyMove> {
+        let mut rng = view.rng();
+        let (best, mistakes) = candidates(view, &mut rng, cancel)?;
+        let mv = match mistakes.choose_weighted(&mut rng, |&(_, weight)| weight) {
+            Ok(&(mistake, _)) if rng.gen::<f32>() >= self.accuracy => mistake,
+            _ => best,
+        };
+        Some(StrategyMove::new(mv))
+    }
+}
+
+/// Registers every level, weakest first.
+pub fn register_levels(registry: &mut StrategyRegistry) {
+    for level in 1..=LEVELS {
+        registry.register(LevelStrategy::new(level));
+    }
+}
+
+/// The level of a strategy name such as "Level 3".
+pub fn level_of(name: &str) -> Option<u8> {
+    LEVEL_NAMES
+        .iter()
+        .position(|&n| n == name)
+        .map(|index| index as u8 + 1)
+}
+
+pub fn level_name(level: u8) -> &'static str {
+    LEVEL_NAMES[usize::from(level.clamp(1, LEVELS) - 1)]
+}
+
+/// The best move known for the side to move, and the mistakes it might
+/// play instead with how likely each is. Small positions are solved exactly,
+/// and mistakes weighted by what they still lead to. Otherwise the line
+/// heuristic's move on plain boards, or the Hard strategy's, counts as best,
+/// and mistakes are weighted by the line heuristic where it applies.
+fn candidates(
+    view: &GameState,
+    rng: &mut impl Rng,
+    cancel: &AtomicBool,
+) -> Option<(GameMove, Vec<(GameMove, u32)>)> {
+    // Plan on the board alone: the other picks of a Simultaneous round are
+    // secret, and trying a move would reveal them
+    let mut base = view.clone();
+    base.round.picks.clear();
+    let view = &base;
+    let moves = view.legal_moves();
+    if let Some(values) = solve_moves(view, &moves, cancel) {
+        let best_rank = values.iter().map(|outcome| outcome.rank()).max()?;
+        let best: Vec<GameMove> = moves
+            .iter()
+            .zip(&values)
+            .filter(|(_, outcome)| outcome.rank() == best_rank)
+            .map(|(&mv, _)| mv)
+            .collect();
+        // Losing to the very next move is too obvious to count as believable
+        let mistakes = moves
+            .iter()
+            .zip(&values)
+            .filter(|(_, outcome)| {
+                outcome.rank() < best_rank
+                    && !matches!(outcome, Outcome::Loss(plies) if *plies <= 2)
+            })
+            .map(|(&mv, outcome)| (mv, outcome_weight(*outcome)))
+            .collect();
+        return Some((*best.choose(rng)?, mistakes));
+    }
+
+    let best = if view.rules.mode == GameMode::Classic && !view.rules.gravity {
+        qubic::line_move(view, moves.clone())?
+    } else {
+        find_hard_move(view)?
+    };
+    let weights: Vec<u32> = if uses_lines(view) {
+        qubic::line_scores(view, &moves)
+            .iter()
+            .map(|score| score.weight + 1)
+            .collect()
+    } else {
+        vec![1; moves.len()]
+    };
+    let mistakes = moves
+        .into_iter()
+        .zip(weights)
+        .filter(|&(mv, _)| mv != best && !gives_opponent_win(view, mv))
+        .collect();
+    Some((best, mistakes))
+}
+
+/// How likely a solved mistake is: one that still wins more than one that
+/// only draws, and that more than a loss.
+fn outcome_weight(outcome: Outcome) -> u32 {
+    match outcome {
+        Outcome::Win(_) => 4,
+        Outcome::Draw(_) => 2,
+        Outcome::Loss(_) => 1,
+    }
+}
+
+/// Whether the line heuristic rates moves sensibly: modes where each player
+/// wants lines of their own marks.
+fn uses_lines(view: &GameState) -> bool {
+    matches!(
+        view.rules.mode,
+        GameMode::Classic | GameMode::Vanishing | GameMode::Qubic
+    )
+}
+
+/// The value of each of `moves` for the side to move, when the position is
+/// small enough to solve.
+fn solve_moves(view: &GameState, moves: &[GameMove], cancel: &AtomicBool) -> Option<Vec<Outcome>> {
+    if !solver::supports(view) || moves.len() > MAX_SOLVED_MOVES {
+        return None;
+    }
+    let mut solver = Solver::with_limit(cancel, SOLVER_POSITIONS);
+    moves
+        .iter()
+        .map(|&mv| {
+            let mut after = view.clone();
+            make_move(&mut after, mv);
+            Some(solver.solve(&after)?.before_move(after.turn == view.turn))
+        })
+        .collect()
+}
+
+/// Plays `games` games of every level against the level below on each
+/// calibration board, alternating who starts, and prints each level's
+/// score: wins plus half the draws.
+pub fn run_calibration(games: usize) {
+    println!("Each level against the one below, {games} games per board:");
+    for level in 2..=LEVELS {
+        let (stronger, weaker) = (LevelStrategy::new(level), LevelStrategy::new(level - 1));
+        let mut scores = Vec::new();
+        for (board, &(size, win_length)) in CALIBRATION_BOARDS.iter().enumerate() {
+            let mut points = 0.0;
+            for game in 0..games {
+                let stronger_starts = game % 2 == 0;
+                let players: [&dyn MoveStrategy; 2] = if stronger_starts {
+                    [&stronger, &weaker]
+                } else {
+                    [&weaker, &stronger]
+                };
+                let seed = (board * games + game) as u64;
+                let stronger_side = if stronger_starts {
+                    Player::X
+                } else {
+                    Player::O
+                };
+                points += match play(players, size, win_length, seed) {
+                    Some(player) if player == stronger_side => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+            }
+            scores.push(100.0 * points / games.max(1) as f32);
+        }
+        let boards: Vec<String> = CALIBRATION_BOARDS
+            .iter()
+            .zip(&scores)
+            .map(|((size, win_length), score)| format!("{size}x{size}/{win_length} {score:.1}%"))
+            .collect();
+        println!(
+            "{} vs {}: {:.1}% ({})",
+            level_name(level),
+            level_name(level - 1),
+            scores.iter().sum::<f32>() / scores.len() as f32,
+            boards.join(", ")
+        );
+    }
+}
+
+/// One Classic game between two strategies, X first; the winner, if any.
+fn play(
+    players: [&dyn MoveStrategy; 2],
+    size: usize,
+    win_length: usize,
+    seed: u64,
+) -> Option<Player> {
+    let mut state = GameState {
+        rules: GameRules {
+            board_size: size,
+            win_length,
+            ..Default::default()
+        }
+        .normalized(),
+        ..Default::default()
+    };
+    state.restart(seed);
+    let cancel = AtomicBool::new(false);
+    while !state.is_over() {
+        let strategy = if state.turn == Player::X {
+            players[0]
+        } else {
+            players[1]
+        };
+        let Some(choice) = strategy.choose_move(&state, state.turn, &cancel) else {
+            let player = state.turn;
+            state.forfeit(player);
+            break;
+        };
+        make_move(&mut state, choice.mv);
+    }
+    state.winner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classic(size: usize, win_length: usize) -> GameState {
+        let mut state = GameState {
+            rules: GameRules {
+                board_size: size,
+                win_length,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.restart(1);
+        state
+    }
+
+    #[test]
+    fn mistakes_never_hand_over_the_game() {
+        // X threatens the bottom row; every mistake of O's still blocks
+        let mut state = classic(3, 3);
+        for mv in [(0, 0), (1, 1), (0, 1)] {
+            make_move(&mut state, GameMove::Place(mv));
+        }
+        let cancel = AtomicBool::new(false);
+        let (best, mistakes) = candidates(&state, &mut state.rng(), &cancel).unwrap();
+        assert_eq!(best, GameMove::Place((0, 2)));
+        assert!(mistakes.is_empty());
+    }
+
+    #[test]
+    fn unsolved_mistakes_favour_better_looking_moves() {
+        let mut state = classic(6, 4);
+        make_move(&mut state, GameMove::Place((2, 2)));
+        let cancel = AtomicBool::new(false);
+        let (_, mistakes) = candidates(&state, &mut state.rng(), &cancel).unwrap();
+        let corner = mistakes
+            .iter()
+            .find(|&&(mv, _)| mv == GameMove::Place((5, 5)))
+            .map(|&(_, weight)| weight)
+            .unwrap();
+        assert!(mistakes.iter().any(|&(_, weight)| weight > corner));
+    }
+
+    #[test]
+    fn secret_picks_dont_change_the_candidates() {
+        let candidates_after = |pick| {
+            let mut state = GameState {
+                rules: GameRules {
+                    mode: GameMode::Simultaneous,
+                    ..Default::default()
+                }
+                .normalized(),
+                ..Default::default()
+            };
+            state.restart(3);
+            for pos in [(0, 0), (1, 0), (0, 1), (2, 2)] {
+                make_move(&mut state, GameMove::Place(pos));
+            }
+            make_move(&mut state, GameMove::Place(pick));
+            assert_eq!(state.turn, Player::O);
+            let cancel = AtomicBool::new(false);
+            candidates(&state, &mut state.rng(), &cancel).unwrap()
+        };
+        // X's pick (0, 2) completes the row; any other pick doesn't
+        assert_eq!(candidates_after((0, 2)), candidates_after((2, 0)));
+    }
+}
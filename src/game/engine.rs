@@ -0,0 +1,254 @@
+//! External engines: any executable speaking the line protocol in
+//! `doc/ENGINE_PROTOCOL.md` can play as a computer player. This binary
+//! itself speaks it with `--engine`, answering with the Hard strategy.
+
+use bevy::prelude::*;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::protocol;
+use super::state::{GameState, Player};
+use super::strategy::{MoveStrategy, StrategyMove, StrategyRegistry};
+use super::systems::HardStrategy;
+
+/// Thinking time offered to engines for each move.
+pub const MOVE_TIME: Duration = Duration::from_millis(1000);
+/// Extra time allowed for the reply to arrive before the engine forfeits.
+const REPLY_GRACE: Duration = Duration::from_millis(500);
+/// Time allowed for an engine to start up and answer the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often a wait for the engine checks whether the move is still wanted.
+const CANCEL_POLL: Duration = Duration::from_millis(20);
+
+/// A computer player backed by a child process. The process starts on
+/// first use and is restarted after it misbehaves.
+pub struct ExternalEngine {
+    name: &'static str,
+    command: Vec<String>,
+    process: Mutex<Option<EngineProcess>>,
+}
+
+impl ExternalEngine {
+    /// `command` is the executable followed by its arguments.
+    pub fn new(name: &'static str, command: Vec<String>) -> Self {
+        Self {
+            name,
+            command,
+            process: Mutex::new(None),
+        }
+    }
+
+    /// Sends the position and reads back the engine's move.
+    fn ask(
+        &self,
+        process: &mut EngineProcess,
+        view: &GameState,
+        cancel: &AtomicBool,
+    ) -> Result<StrategyMove, String> {
+        process.send(&protocol::encode_rules(&view.rules))?;
+        process.send(&protocol::encode_position(view))?;
+        process.send(&format!("go movetime={}", MOVE_TIME.as_millis()))?;
+        let reply = process.expect("bestmove", MOVE_TIME + REPLY_GRACE, cancel)?;
+
+        // bestmove <move tokens> [eval <value>]
+        let reply = reply.trim_start_matches("bestmove").trim();
+        let (text, evaluation) = match reply.split_once(" eval ") {
+            Some((text, eval)) => (text, eval.trim().parse().ok()),
+            None => (reply, None),
+        };
+        let mv = protocol::decode_move(text)?;
+        Ok(StrategyMove { mv, evaluation })
+    }
+}
+
+impl MoveStrategy for ExternalEngine {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Any failure (the engine can't start, crashes, times out or answers
+    /// nonsense) returns no move, so the engine's side forfeits.
+    fn choose_move(
+        &self,
+        view: &GameState,
+        _side: Player,
+        cancel: &AtomicBool,
+    ) -> Option<StrategyMove> {
+        let mut slot = self.process.lock().ok()?;
+        if slot.is_none() {
+            match EngineProcess::spawn(&self.command, cancel) {
+                Ok(process) => *slot = Some(process),
+                Err(err) => {
+                    warn!("Engine {} failed to start: {err}", self.name);
+                    return None;
+                }
+            }
+        }
+        let process = slot.as_mut()?;
+        match self.ask(process, view, cancel) {
+            Ok(choice) => Some(choice),
+            Err(err) => {
+                if !cancel.load(Ordering::Relaxed) {
+                    warn!("Engine {} misbehaved: {err}", self.name);
+                }
+                // Dropping the process kills it; the next game starts afresh.
+                // A cancelled engine goes too, or its late reply would be
+                // taken for the next move
+                *slot = None;
+                None
+            }
+        }
+    }
+}
+
+/// Offers the reference engine (this executable run with `--engine`) and
+/// every `--bot` command as computer players, named after their executable.
+pub fn register_engines(registry: &mut StrategyRegistry, bots: &[String]) {
+    if let Ok(exe) = std::env::current_exe() {
+        let command = vec![exe.display().to_string(), "--engine".to_string()];
+        registry.register(ExternalEngine::new("Reference", command));
+    }
+    for bot in bots {
+        let command: Vec<String> = bot.split_whitespace().map(String::from).collect();
+        let Some(program) = command.first() else {
+            continue;
+        };
+        let name = Path::new(program)
+            .file_stem()
+            .map_or_else(|| program.clone(), |s| s.to_string_lossy().into_owned());
+        // Strategy names live for the whole run
+        registry.register(ExternalEngine::new(name.leak(), command));
+    }
+}
+
+struct EngineProcess {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+}
+
+impl EngineProcess {
+    fn spawn(command: &[String], cancel: &AtomicBool) -> Result<Self, String> {
+        let (program, args) = command.split_first().ok_or("empty command")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| err.to_string())?;
+        let stdin = child.stdin.take().ok_or("no stdin")?;
+        let stdout = child.stdout.take().ok_or("no stdout")?;
+
+        // Lines are read on their own thread so replies can time out
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut process = Self {
+            child,
+            stdin,
+            lines,
+        };
+        process.send("ttt")?;
+        process.expect("tttok", HANDSHAKE_TIMEOUT, cancel)?;
+        Ok(process)
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.stdin, "{line}")
+            .and_then(|_| self.stdin.flush())
+            .map_err(|err| format!("write failed: {err}"))
+    }
+
+    /// Waits for a line starting with `command`, skipping any others such as
+    /// `info` lines, until the timeout or until `cancel` is set.
+    fn expect(
+        &self,
+        command: &str,
+        timeout: Duration,
+        cancel: &AtomicBool,
+    ) -> Result<String, String> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Err("cancelled".to_string());
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.lines.recv_timeout(remaining.min(CANCEL_POLL)) {
+                Ok(line) if line.split_whitespace().next() == Some(command) => return Ok(line),
+                Ok(_) => {}
+                Err(RecvTimeoutError::Timeout) if remaining > CANCEL_POLL => {}
+                Err(RecvTimeoutError::Timeout) => return Err(format!("no {command} in time")),
+                Err(RecvTimeoutError::Disconnected) => return Err("engine exited".to_string()),
+            }
+        }
+    }
+}
+
+impl Drop for EngineProcess {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The reference engine: speaks the protocol on stdin and stdout and plays
+/// with the Hard strategy.
+pub fn run_reference_engine() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut rules = None;
+    let mut position: Option<GameState> = None;
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let command = line.split_whitespace().next().unwrap_or("");
+        match command {
+            "ttt" => {
+                writeln!(stdout, "id name Reference")?;
+                writeln!(stdout, "tttok")?;
+            }
+            "isready" => writeln!(stdout, "readyok")?,
+            "rules" => match protocol::decode_rules(&line) {
+                Ok(decoded) => rules = Some(decoded),
+                Err(err) => writeln!(stdout, "info error {err}")?,
+            },
+            "position" => {
+                let decoded = rules
+                    .ok_or_else(|| "position before rules".to_string())
+                    .and_then(|rules| protocol::decode_position(&rules, &line));
+                match decoded {
+                    Ok(state) => position = Some(state),
+                    Err(err) => writeln!(stdout, "info error {err}")?,
+                }
+            }
+            "go" => {
+                let choice = position.as_ref().and_then(|state| {
+                    HardStrategy.choose_move(state, state.turn, &AtomicBool::new(false))
+                });
+                match choice {
+                    Some(choice) => {
+                        writeln!(stdout, "bestmove {}", protocol::encode_move(choice.mv))?
+                    }
+                    None => writeln!(stdout, "bestmove none")?,
+                }
+            }
+            "quit" => break,
+            _ => {}
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
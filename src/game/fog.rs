@@ -0,0 +1,158 @@
+use super::components::PlayerType;
+use super::state::{GameMode, GameState, Player, PlayerConfig};
+
+/// Whether `viewer` can see the mark in `pos`: their own marks and revealed
+/// cells. `None` sees everything, as outside fog-of-war games.
+pub fn is_visible(state: &GameState, viewer: Option<Player>, pos: (usize, usize)) -> bool {
+    match (viewer, state.board[pos]) {
+        (Some(player), Some(symbol)) => symbol.owner() == player || state.revealed[pos],
+        _ => true,
+    }
+}
+
+/// Whether `pos` holds a mark hidden from `player`; placing there probes it.
+pub fn is_hidden_from(state: &GameState, player: Player, pos: (usize, usize)) -> bool {
+    state.rules.fog && !is_visible(state, Some(player), pos)
+}
+
+/// The board as `player` knows it, for the computer to plan on: hidden marks
+/// and the secret picks of a Simultaneous round are left out. The move
+/// history would give them away, so it is left out too.
+pub fn view_for(state: &GameState, player: Player) -> GameState {
+    let mut view = state.clone();
+    if state.rules.mode == GameMode::Simultaneous {
+        view.round.picks.clear();
+        view.history.clear();
+        view.hints.clear();
+    }
+    if state.rules.fog {
+        for (pos, _) in state.board.iter() {
+            if is_hidden_from(state, player, pos) {
+                view.board[pos] = None;
+                view.placed_at[pos] = 0;
+            }
+        }
+        view.history.clear();
+        view.hints.clear();
+        view.opening = None;
+        view.last_move = None;
+    }
+    view
+}
+
+/// A placement on a hidden mark: the mark is revealed to everyone and the
+/// turn passes without a move.
+pub fn probe(state: &mut GameState, pos: (usize, usize)) {
+    state.revealed[pos] = true;
+    state.last_move = Some(pos);
+    state.turn = state.next_player(state.turn);
+    state.curtain = true;
+}
+
+/// Marks placed before the first move are known to everyone.
+pub fn reveal_marks(state: &mut GameState) {
+    for (pos, cell) in state.board.iter() {
+        if cell.is_some() {
+            state.revealed[pos] = true;
+        }
+    }
+}
+
+/// Whose marks the screen shows: the human to move, otherwise the first
+/// human in turn order. `None` shows the whole board, as when only
+/// computers play or outside fog-of-war games.
+pub fn viewer(state: &GameState, config: &PlayerConfig) -> Option<Player> {
+    if !state.rules.fog || state.setup_phase {
+        return None;
+    }
+    let mut humans = state
+        .players()
+        .into_iter()
+        .filter(|&player| config.type_of(player) == PlayerType::Human);
+    if config.type_of(state.turn) == PlayerType::Human {
+        Some(state.turn)
+    } else {
+        humans.next()
+    }
+}
+
+/// Whether the "pass the device" curtain hides the board; only when several
+/// humans share the screen.
+pub fn curtain_shown(state: &GameState, config: &PlayerConfig) -> bool {
+    let humans = state
+        .players()
+        .into_iter()
+        .filter(|&player| config.type_of(player) == PlayerType::Human)
+        .count();
+    state.curtain
+        && !state.setup_phase
+        && !state.is_over()
+        && humans >= 2
+        && config.type_of(state.turn) == PlayerType::Human
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::{GameMove, GameRules, PositionKey};
+    use crate::game::systems::make_move;
+
+    fn fog() -> GameState {
+        let mut state = GameState {
+            rules: GameRules {
+                fog: true,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        state
+    }
+
+    #[test]
+    fn view_keeps_hidden_marks_out() {
+        let mut state = fog();
+        make_move(&mut state, GameMove::Place((1, 1)));
+
+        let view = view_for(&state, Player::O);
+        assert_eq!(view.board[(1, 1)], None);
+        assert_eq!(view.placed_at[(1, 1)], 0);
+        assert!(view.history.is_empty());
+        assert_eq!(view.last_move, None);
+        assert!(view.opening.is_none());
+
+        let own = view_for(&state, Player::X);
+        assert!(own.board[(1, 1)].is_some());
+    }
+
+    #[test]
+    fn probing_changes_the_position() {
+        let mut state = fog();
+        make_move(&mut state, GameMove::Place((1, 1)));
+        let before = PositionKey::of(&state);
+        make_move(&mut state, GameMove::Place((1, 1)));
+        assert_eq!(state.moves, 1);
+        assert!(state.revealed[(1, 1)]);
+        assert_ne!(PositionKey::of(&state), before);
+    }
+
+    #[test]
+    fn view_keeps_secret_picks_out() {
+        let mut state = GameState {
+            rules: GameRules {
+                mode: GameMode::Simultaneous,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        make_move(&mut state, GameMove::Place((2, 0)));
+        assert_eq!(state.round.picks.len(), 1);
+
+        let view = view_for(&state, Player::O);
+        assert!(view.round.picks.is_empty());
+        assert!(view.history.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! Move hints for human players: the exact solver, or the strongest
+//! registered strategy where it can't solve the position, picks a move and
+//! `reason` sums up why in a few words.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use super::solver;
+use super::state::{GameMode, GameMove, GameState, Player, WinningLine};
+use super::strategy::{MoveStrategy, StrategyMove, StrategyRegistry};
+use super::systems::{find_winning_move, make_move};
+
+/// Strategies asked for hints the solver can't give, strongest first.
+const HINT_STRATEGIES: [&str; 2] = ["MCTS", "Hard"];
+
+/// Positions the solver may visit for a hint before the fallback takes over.
+const SOLVER_POSITIONS: usize = 20_000;
+
+/// Positions with more moves open than this go straight to the fallback.
+const MAX_SOLVED_MOVES: usize = 12;
+
+/// A hint shown during the game, kept with the move history.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HintRecord {
+    /// Moves played before the hint was shown.
+    pub ply: usize,
+    pub player: Player,
+    pub mv: GameMove,
+}
+
+/// Solves the position when it is small enough, and asks `fallback`
+/// otherwise.
+struct SolverFirst {
+    fallback: Arc<dyn MoveStrategy>,
+}
+
+impl MoveStrategy for SolverFirst {
+    fn name(&self) -> &'static str {
+        self.fallback.name()
+    }
+
+    fn choose_move(
+        &self,
+        view: &GameState,
+        side: Player,
+        cancel: &AtomicBool,
+    ) -> Option<StrategyMove> {
+        let solved = if view.legal_moves().len() <= MAX_SOLVED_MOVES {
+            solver::best_move(view, cancel, SOLVER_POSITIONS)
+        } else {
+            None
+        };
+        solved.or_else(|| self.fallback.choose_move(view, side, cancel))
+    }
+}
+
+/// The strongest strategy available for hints.
+pub fn strongest(registry: &StrategyRegistry) -> Option<(&'static str, Arc<dyn MoveStrategy>)> {
+    HINT_STRATEGIES.iter().find_map(|&name| {
+        let fallback = registry.get(name)?;
+        Some((
+            name,
+            Arc::new(SolverFirst { fallback }) as Arc<dyn MoveStrategy>,
+        ))
+    })
+}
+
+/// A short explanation of `choice` for the side to move, such as
+/// "blocks O's column".
+pub fn reason(state: &GameState, choice: &StrategyMove) -> String {
+    let player = state.turn;
+    let mv = choice.mv;
+
+    // Picks are resolved together, so there is nothing to win or block yet
+    if state.rules.mode != GameMode::Simultaneous {
+        let mut after = state.clone();
+        make_move(&mut after, mv);
+        if after.winner == Some(player) {
+            return "wins the game".to_string();
+        }
+
+        if matches!(
+            mv,
+            GameMove::Place(_) | GameMove::PlaceSymbol(..) | GameMove::PlaceOn(..)
+        ) {
+            for opponent in state.opponents(player) {
+                let mut theirs = state.clone();
+                theirs.turn = opponent;
+                if !theirs.legal_moves().contains(&mv) {
+                    continue;
+                }
+                make_move(&mut theirs, mv);
+                if theirs.winner == Some(opponent) {
+                    let line = theirs
+                        .winning_line
+                        .as_ref()
+                        .map_or("line", |line| line_name(state, line));
+                    return format!("blocks {}'s {line}", opponent.letter());
+                }
+            }
+        }
+
+        if !after.is_over() && find_winning_move(&after, player).is_some() {
+            return "threatens to win next move".to_string();
+        }
+    }
+
+    match choice.evaluation {
+        Some(evaluation) if evaluation > 0.5 => "keeps a winning position".to_string(),
+        Some(evaluation) if evaluation < -0.5 => "the best defence in a tough spot".to_string(),
+        _ => "the engine's strongest move".to_string(),
+    }
+}
+
+/// "row", "column", "diagonal" or just "line" in 3D and on hex boards.
+pub fn line_name(state: &GameState, line: &WinningLine) -> &'static str {
+    let step = line.step;
+    if state.rules.hex || step.z != 0 {
+        "line"
+    } else if step.y == 0 {
+        "row"
+    } else if step.x == 0 {
+        "column"
+    } else {
+        "diagonal"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::GameRules;
+    use crate::game::systems::HardStrategy;
+
+    fn classic(size: usize, win_length: usize) -> GameState {
+        let mut state = GameState {
+            rules: GameRules {
+                board_size: size,
+                win_length,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        state
+    }
+
+    #[test]
+    fn small_boards_are_solved() {
+        // O must take the centre after X's corner; anything else loses
+        let mut state = classic(3, 3);
+        make_move(&mut state, GameMove::Place((0, 0)));
+        let hint = SolverFirst {
+            fallback: Arc::new(HardStrategy),
+        };
+        let choice = hint
+            .choose_move(&state, state.turn, &AtomicBool::new(false))
+            .unwrap();
+        assert_eq!(choice.mv, GameMove::Place((1, 1)));
+        assert_eq!(choice.evaluation, Some(0.0));
+        assert_eq!(reason(&state, &choice), "the engine's strongest move");
+    }
+
+    #[test]
+    fn big_boards_fall_back() {
+        let state = classic(7, 5);
+        let hint = SolverFirst {
+            fallback: Arc::new(HardStrategy),
+        };
+        assert!(hint
+            .choose_move(&state, state.turn, &AtomicBool::new(false))
+            .is_some());
+    }
+}
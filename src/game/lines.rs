@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use super::state::{GameState, Symbol, WinningLine};
+
+/// A winning line as `(layer, row, col)` cells; flat boards only use layer 0.
+pub type Line = Vec<(usize, usize, usize)>;
+
+/// Every run of `win_length` cells on a stack of `layers` square boards of
+/// side `size`, in all 13 directions through the stack. A single layer only
+/// has the 4 flat directions: rows, columns and both diagonals. With `wrap`,
+/// rows and columns continue across the edges; layers never wrap.
+pub fn line_table(layers: usize, size: usize, win_length: usize, wrap: bool) -> Vec<Line> {
+    lines_along(&directions(), layers, size, win_length, wrap)
+}
+
+/// Every run of `win_length` cells on a hex board along the three hex axes.
+/// In axial coordinates (`row = r`, `col = q`) those are rows, columns and
+/// the diagonal where `row` rises as `col` falls.
+pub fn hex_line_table(size: usize, win_length: usize, wrap: bool) -> Vec<Line> {
+    lines_along(
+        &[(0, 0, 1), (0, 1, 0), (0, 1, -1)],
+        1,
+        size,
+        win_length,
+        wrap,
+    )
+}
+
+fn lines_along(
+    directions: &[(isize, isize, isize)],
+    layers: usize,
+    size: usize,
+    win_length: usize,
+    wrap: bool,
+) -> Vec<Line> {
+    let steps = win_length as isize - 1;
+    let in_range = |v: isize, len: usize| (0..len as isize).contains(&v);
+    let on_board = |v: isize| {
+        if wrap {
+            Some(v.rem_euclid(size as isize) as usize)
+        } else {
+            in_range(v, size).then_some(v as usize)
+        }
+    };
+    // A wrapped line as long as the board is found once per starting cell
+    let mut seen = HashSet::new();
+    let mut lines = Vec::new();
+    for &(dl, dr, dc) in directions {
+        for layer in 0..layers {
+            for row in 0..size {
+                for col in 0..size {
+                    let line: Option<Line> = (0..=steps)
+                        .map(|i| {
+                            let l = layer as isize + dl * i;
+                            let r = row as isize + dr * i;
+                            let c = col as isize + dc * i;
+                            let l = in_range(l, layers).then_some(l as usize)?;
+                            Some((l, on_board(r)?, on_board(c)?))
+                        })
+                        .collect();
+                    let Some(line) = line else {
+                        continue;
+                    };
+                    let mut cells = line.clone();
+                    cells.sort_unstable();
+                    if seen.insert(cells) {
+                        lines.push(line);
+                    }
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// One of each pair of opposite directions: the first non-zero step is positive.
+fn directions() -> Vec<(isize, isize, isize)> {
+    let mut dirs = Vec::new();
+    for dl in -1..=1 {
+        for dr in -1..=1 {
+            for dc in -1..=1 {
+                if [dl, dr, dc].into_iter().find(|&d| d != 0) == Some(1) {
+                    dirs.push((dl, dr, dc));
+                }
+            }
+        }
+    }
+    dirs
+}
+
+/// Finds a line of filled cells that satisfies the mode's line rule.
+pub fn check_winner(state: &GameState) -> Option<WinningLine> {
+    let rule = state.rules.mode.line_rule();
+    state.lines.iter().find_map(|line| {
+        let symbols: Option<Vec<Symbol>> = line.iter().map(|&cell| state.symbol_at(cell)).collect();
+        symbols
+            .filter(|symbols| rule.completes(symbols))
+            .map(|_| WinningLine::spanning(line))
+    })
+}
@@ -0,0 +1,248 @@
+//! Monte Carlo Tree Search: plays random games from the current position
+//! and grows a search tree towards the moves that win most often. Needs no
+//! knowledge of the rules beyond `GameState::legal_moves` and `make_move`,
+//! so it handles any board size, win length and rule variant.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::simultaneous;
+use super::state::{GameMode, GameMove, GameState, Player};
+use super::strategy::{MoveStrategy, StrategyMove};
+use super::systems::make_move;
+
+/// Random games longer than this count as draws; vanishing marks and
+/// sliding can otherwise go on for a long time.
+const PLAYOUT_MOVE_LIMIT: usize = 200;
+
+/// Search budget and tuning.
+#[derive(Clone, Copy, Debug)]
+pub struct MctsConfig {
+    /// Playouts per move.
+    pub iterations: usize,
+    /// Most thinking time per move, when set; whichever limit is hit first
+    /// ends the search. Off by default, as stopping on the clock would let
+    /// the machine's speed change the moves of a seeded game.
+    pub time_budget: Option<Duration>,
+    /// UCT exploration constant: higher tries more unpromising moves.
+    pub exploration: f32,
+    /// Fixed seed overriding the game's own seed.
+    pub seed: Option<u64>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 5_000,
+            time_budget: None,
+            exploration: std::f32::consts::SQRT_2,
+            seed: None,
+        }
+    }
+}
+
+pub struct MctsStrategy {
+    pub config: MctsConfig,
+}
+
+impl MoveStrategy for MctsStrategy {
+    fn name(&self) -> &'static str {
+        "MCTS"
+    }
+
+    fn choose_move(
+        &self,
+        view: &GameState,
+        _side: Player,
+        cancel: &AtomicBool,
+    ) -> Option<StrategyMove> {
+        // Picks are secret in Simultaneous mode, so searching the opponent's
+        // reply to our pick would be cheating; it has its own solver
+        if view.rules.mode == GameMode::Simultaneous {
+            return simultaneous::best_move(view).map(StrategyMove::new);
+        }
+        search(view, &self.config, cancel)
+    }
+}
+
+struct Node {
+    mv: Option<GameMove>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<GameMove>,
+    /// Player who made `mv`; rewards are counted from their side.
+    mover: Player,
+    visits: u32,
+    reward: f32,
+}
+
+impl Node {
+    fn new(state: &GameState, mv: Option<GameMove>, parent: Option<usize>, mover: Player) -> Self {
+        Self {
+            mv,
+            parent,
+            children: Vec::new(),
+            untried: if state.is_over() {
+                Vec::new()
+            } else {
+                state.legal_moves()
+            },
+            mover,
+            visits: 0,
+            reward: 0.0,
+        }
+    }
+
+    fn win_rate(&self) -> f32 {
+        self.reward / self.visits.max(1) as f32
+    }
+}
+
+/// Runs the search and returns the most visited move, with the win rate
+/// behind it as the evaluation. Setting `cancel` ends the search early.
+pub fn search(
+    root_state: &GameState,
+    config: &MctsConfig,
+    cancel: &AtomicBool,
+) -> Option<StrategyMove> {
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => root_state.rng(),
+    };
+    let start = Instant::now();
+    let mut nodes = vec![Node::new(root_state, None, None, root_state.turn)];
+    nodes[0].untried.shuffle(&mut rng);
+
+    for _ in 0..config.iterations {
+        let out_of_time = config
+            .time_budget
+            .is_some_and(|budget| start.elapsed() >= budget);
+        if out_of_time || cancel.load(Ordering::Relaxed) {
+            break;
+        }
+
+        // Selection: follow the best UCT child while every move was tried
+        let mut index = 0;
+        let mut state = root_state.clone();
+        while nodes[index].untried.is_empty() && !nodes[index].children.is_empty() {
+            let parent_visits = nodes[index].visits.max(1) as f32;
+            index = *nodes[index]
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let uct = |i: usize| {
+                        let node = &nodes[i];
+                        node.win_rate()
+                            + config.exploration
+                                * (parent_visits.ln() / node.visits.max(1) as f32).sqrt()
+                    };
+                    uct(a).total_cmp(&uct(b))
+                })
+                .expect("children checked above");
+            if let Some(mv) = nodes[index].mv {
+                make_move(&mut state, mv);
+            }
+        }
+
+        // Expansion: add one untried move
+        if let Some(mv) = nodes[index].untried.pop() {
+            let mover = state.turn;
+            make_move(&mut state, mv);
+            let mut child = Node::new(&state, Some(mv), Some(index), mover);
+            child.untried.shuffle(&mut rng);
+            nodes.push(child);
+            let child_index = nodes.len() - 1;
+            nodes[index].children.push(child_index);
+            index = child_index;
+        }
+
+        // Simulation: random moves to the end of the game
+        let mut moves = 0;
+        while !state.is_over() && moves < PLAYOUT_MOVE_LIMIT {
+            let Some(&mv) = state.legal_moves().choose(&mut rng) else {
+                break;
+            };
+            make_move(&mut state, mv);
+            moves += 1;
+        }
+
+        // Backpropagation: a win for the mover scores 1, a draw 1/2
+        let mut current = Some(index);
+        while let Some(i) = current {
+            let node = &mut nodes[i];
+            node.visits += 1;
+            node.reward += match state.winner {
+                Some(winner) if winner == node.mover => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            current = node.parent;
+        }
+    }
+
+    let best = nodes[0]
+        .children
+        .iter()
+        .map(|&i| &nodes[i])
+        .max_by_key(|node| node.visits)?;
+    Some(StrategyMove {
+        mv: best.mv?,
+        evaluation: Some(best.win_rate() * 2.0 - 1.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::GameRules;
+
+    fn config() -> MctsConfig {
+        MctsConfig {
+            iterations: usize::MAX,
+            time_budget: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn cancelled_search_stops_at_once() {
+        let mut state = GameState {
+            rules: GameRules {
+                board_size: 7,
+                win_length: 5,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        let start = Instant::now();
+        search(&state, &config(), &AtomicBool::new(true));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn same_seed_same_move() {
+        let mut state = GameState {
+            rules: GameRules {
+                board_size: 5,
+                win_length: 4,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.restart(7);
+        let config = MctsConfig {
+            iterations: 500,
+            ..Default::default()
+        };
+        let cancel = AtomicBool::new(false);
+        let first = search(&state, &config, &cancel);
+        assert!(first.is_some());
+        assert_eq!(search(&state, &config, &cancel), first);
+    }
+}
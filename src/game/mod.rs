@@ -1,22 +1,63 @@
 use bevy::prelude::*;
 
+mod board;
+mod cli;
+mod coach;
 mod components;
+mod difficulty;
+mod engine;
+mod fog;
+mod hint;
+mod lines;
+mod mcts;
+mod notakto;
+mod protocol;
+mod quantum;
+mod qubic;
+mod review;
+mod setup;
+mod simultaneous;
+mod solver;
 mod state;
+mod strategy;
 mod systems;
 mod utils;
 
+pub use cli::CliArgs;
+pub use difficulty::run_calibration;
+pub use engine::run_reference_engine;
 use state::AppState;
 
-pub struct GamePlugin;
+pub struct GamePlugin {
+    /// External engine commands given with `--bot`.
+    pub bots: Vec<String>,
+    /// MCTS search budget and tuning from the command line.
+    pub mcts: mcts::MctsConfig,
+    /// Seed of the first game from `--seed`; random when `None`.
+    pub seed: Option<u64>,
+}
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
+        let mut strategies = systems::builtin_strategies();
+        strategies.register(mcts::MctsStrategy { config: self.mcts });
+        difficulty::register_levels(&mut strategies);
+        engine::register_engines(&mut strategies, &self.bots);
+
         app.insert_resource(ClearColor(state::BG_COLOR))
             .init_state::<AppState>()
             .init_resource::<state::GameState>()
+            .init_resource::<state::GameRules>()
             .init_resource::<state::PlayerConfig>()
+            .init_resource::<state::MoveSelection>()
             .init_resource::<state::Score>()
+            .insert_resource(state::GameRng::new(self.seed))
             .init_resource::<state::ComputerMoveTimer>()
+            .init_resource::<state::ComputerThinking>()
+            .init_resource::<state::HintState>()
+            .init_resource::<state::AnalysisOverlay>()
+            .init_resource::<state::GameReview>()
+            .insert_resource(strategies)
             .add_systems(Startup, systems::setup_camera)
             // Menu state systems
             .add_systems(
@@ -25,7 +66,14 @@ impl Plugin for GamePlugin {
             )
             .add_systems(
                 Update,
-                (systems::handle_menu_buttons, systems::animate_logo)
+                (
+                    systems::handle_menu_buttons,
+                    systems::handle_difficulty_buttons,
+                    systems::handle_option_buttons,
+                    systems::update_option_labels,
+                    systems::update_player_labels,
+                    systems::animate_logo,
+                )
                     .run_if(in_state(AppState::Menu)),
             )
             .add_systems(
@@ -35,20 +83,50 @@ impl Plugin for GamePlugin {
             // Playing state systems
             .add_systems(
                 OnEnter(AppState::Playing),
-                (systems::spawn_grid, systems::spawn_scoreboard),
+                (
+                    systems::start_game,
+                    systems::spawn_grid,
+                    systems::spawn_scoreboard,
+                )
+                    .chain(),
             )
             .add_systems(
                 Update,
                 (
                     systems::handle_clicks,
                     systems::computer_player,
+                    systems::update_column_hover,
                     systems::draw_marks,
+                    systems::draw_move_hints,
+                    systems::animate_falling.after(systems::draw_marks),
                     systems::draw_win_highlight,
                     systems::update_score.before(systems::update_scoreboard),
                     systems::update_scoreboard,
                     systems::show_game_over_ui,
                     systems::handle_game_over_buttons,
                     systems::keyboard_controls,
+                    systems::show_collapse_prompt,
+                    systems::handle_collapse_buttons,
+                    systems::show_number_picker,
+                    systems::handle_number_buttons,
+                    systems::show_setup_prompt,
+                    systems::handle_setup_buttons,
+                    (
+                        systems::show_curtain,
+                        systems::handle_curtain,
+                        systems::animate_reveal.after(systems::draw_marks),
+                        systems::show_round_prompt,
+                        systems::update_thinking_indicator,
+                        systems::update_seed_text,
+                        systems::request_hint,
+                        systems::show_hint.after(systems::request_hint),
+                        systems::update_hint_button,
+                        systems::update_analysis,
+                        systems::handle_review_input,
+                        systems::update_review.after(systems::handle_review_input),
+                        systems::handle_coach_input,
+                        systems::show_coach_prompt.after(systems::handle_coach_input),
+                    ),
                 )
                     .run_if(in_state(AppState::Playing)),
             )
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+
+use super::board::Board;
+use super::state::{GameMove, GameState, Symbol};
+
+/// Notakto is always played on 3x3 boards; cells are bits `row * 3 + col`.
+const SIZE: usize = 3;
+
+const LINES: [u16; 8] = [
+    0b000_000_111,
+    0b000_111_000,
+    0b111_000_000,
+    0b001_001_001,
+    0b010_010_010,
+    0b100_100_100,
+    0b100_010_001,
+    0b001_010_100,
+];
+
+fn mask(board: &Board<Option<Symbol>>) -> u16 {
+    board
+        .iter()
+        .filter(|(_, cell)| cell.is_some())
+        .fold(0, |m, ((row, col), _)| m | 1 << (row * SIZE + col))
+}
+
+fn mask_is_dead(mask: u16) -> bool {
+    LINES.iter().any(|&line| (mask & line).count_ones() == 3)
+}
+
+/// A board with three in a row is dead and can't be played on.
+pub fn is_dead(board: &Board<Option<Symbol>>) -> bool {
+    mask_is_dead(mask(board))
+}
+
+pub fn legal_moves(state: &GameState) -> Vec<GameMove> {
+    state
+        .boards
+        .iter()
+        .enumerate()
+        .filter(|(_, board)| !is_dead(board))
+        .flat_map(|(index, board)| {
+            board
+                .iter()
+                .filter(|(_, cell)| cell.is_none())
+                .map(move |(pos, _)| GameMove::PlaceOn(index, pos))
+        })
+        .collect()
+}
+
+/// Both players place X; whoever kills the last live board loses.
+pub fn play(state: &mut GameState, index: usize, pos: (usize, usize)) {
+    state.boards[index][pos] = Some(Symbol::X);
+    state.moves += 1;
+
+    if state.boards.iter().all(is_dead) {
+        state.winner = Some(state.turn.other());
+    } else {
+        state.turn = state.turn.other();
+    }
+}
+
+/// Optimal play from an exhaustive search over live boards, reduced by
+/// symmetry. When every move loses, avoids finishing the last board early.
+pub fn best_move(state: &GameState) -> Option<GameMove> {
+    let mut moves = legal_moves(state);
+    moves.shuffle(&mut state.rng());
+
+    let masks: Vec<u16> = state.boards.iter().map(mask).collect();
+    let mut memo = HashMap::new();
+    let mut fallback = None;
+    for &mv in &moves {
+        let GameMove::PlaceOn(index, (row, col)) = mv else {
+            continue;
+        };
+        let mut after = masks.clone();
+        after[index] |= 1 << (row * SIZE + col);
+        let live = live_key(&after);
+        if live.is_empty() {
+            // Kills the last board: a loss
+            continue;
+        }
+        if !is_win(live, &mut memo) {
+            return Some(mv);
+        }
+        fallback.get_or_insert(mv);
+    }
+    fallback.or(moves.first().copied())
+}
+
+/// Whether the player to move wins with these live boards.
+fn is_win(live: Vec<u16>, memo: &mut HashMap<Vec<u16>, bool>) -> bool {
+    if let Some(&win) = memo.get(&live) {
+        return win;
+    }
+    let mut win = false;
+    'search: for (i, &board) in live.iter().enumerate() {
+        for bit in (0..SIZE * SIZE).filter(|&b| board & 1 << b == 0) {
+            let mut after = live.clone();
+            after[i] |= 1 << bit;
+            let next = live_key(&after);
+            if !next.is_empty() && !is_win(next, memo) {
+                win = true;
+                break 'search;
+            }
+        }
+    }
+    memo.insert(live, win);
+    win
+}
+
+/// Canonical key for a set of boards: dead boards dropped, each board reduced
+/// to its smallest symmetric image, and the boards sorted.
+fn live_key(masks: &[u16]) -> Vec<u16> {
+    let mut live: Vec<u16> = masks
+        .iter()
+        .filter(|&&m| !mask_is_dead(m))
+        .map(|&m| canonical(m))
+        .collect();
+    live.sort_unstable();
+    live
+}
+
+/// Maps a `(row, col)` cell to its image under one of the board's symmetries.
+type Symmetry = fn(usize, usize) -> (usize, usize);
+
+fn canonical(mask: u16) -> u16 {
+    let transforms: [Symmetry; 8] = [
+        |r, c| (r, c),
+        |r, c| (c, 2 - r),
+        |r, c| (2 - r, 2 - c),
+        |r, c| (2 - c, r),
+        |r, c| (r, 2 - c),
+        |r, c| (2 - r, c),
+        |r, c| (c, r),
+        |r, c| (2 - c, 2 - r),
+    ];
+    transforms
+        .iter()
+        .map(|transform| {
+            (0..SIZE * SIZE)
+                .filter(|&bit| mask & 1 << bit != 0)
+                .fold(0u16, |m, bit| {
+                    let (r, c) = transform(bit / SIZE, bit % SIZE);
+                    m | 1 << (r * SIZE + c)
+                })
+        })
+        .min()
+        .unwrap_or(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::{GameMode, GameRules, Player};
+    use crate::game::systems::make_move;
+
+    #[test]
+    fn killing_the_last_board_loses() {
+        let mut state = GameState {
+            rules: GameRules {
+                mode: GameMode::Notakto,
+                board_count: 2,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        for mv in [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1)] {
+            make_move(&mut state, GameMove::PlaceOn(mv.0, (0, mv.1)));
+        }
+        assert!(is_dead(&state.boards[0]));
+        assert!(!state.is_over());
+
+        // O completes the second board's row and loses
+        assert_eq!(state.turn, Player::O);
+        make_move(&mut state, GameMove::PlaceOn(1, (0, 2)));
+        assert_eq!(state.winner, Some(Player::X));
+    }
+}
@@ -0,0 +1,500 @@
+//! Text encoding of rules, positions and moves for the engine protocol
+//! described in `doc/ENGINE_PROTOCOL.md`. Every message is one line of
+//! space-separated tokens; rules and positions use `key=value` tokens.
+
+use super::board::Board;
+use super::quantum::{PendingCollapse, SpookyMark};
+use super::state::{
+    GameMode, GameMove, GameRules, GameState, Player, Setup, Symbol, MAX_BOARD_COUNT,
+    MAX_BOARD_SIZE,
+};
+
+/// `rules mode=Classic size=3 win=3 ...`
+pub fn encode_rules(rules: &GameRules) -> String {
+    let players: String = if rules.three_players {
+        rules.turn_order.iter().map(|p| p.letter()).collect()
+    } else {
+        "XO".to_string()
+    };
+    format!(
+        "rules mode={:?} size={} win={} gravity={} hex={} wrap={} boards={} players={} fog={}",
+        rules.mode,
+        rules.board_size,
+        rules.win_length,
+        u8::from(rules.gravity),
+        u8::from(rules.hex),
+        u8::from(rules.wrap),
+        rules.board_count,
+        players,
+        u8::from(rules.fog),
+    )
+}
+
+pub fn decode_rules(line: &str) -> Result<GameRules, String> {
+    let mut rules = GameRules::default();
+    for (key, value) in fields(line, "rules")? {
+        let flag = || value == "1";
+        match key {
+            "mode" => rules.mode = parse_mode(value)?,
+            "size" => rules.board_size = parse_number(value)?,
+            "win" => rules.win_length = parse_number(value)?,
+            "gravity" => rules.gravity = flag(),
+            "hex" => rules.hex = flag(),
+            "wrap" => rules.wrap = flag(),
+            "boards" => rules.board_count = parse_number(value)?,
+            "fog" => rules.fog = flag(),
+            "players" => {
+                let order: Vec<Player> =
+                    value.chars().map(parse_player).collect::<Result<_, _>>()?;
+                match order.as_slice() {
+                    [a, b, c] => {
+                        rules.three_players = true;
+                        rules.turn_order = [*a, *b, *c];
+                    }
+                    [_, _] => rules.three_players = false,
+                    _ => return Err(format!("bad player list {value}")),
+                }
+            }
+            // Unknown keys are ignored so the protocol can grow
+            _ => {}
+        }
+    }
+    check_shape(&rules)?;
+    Ok(rules)
+}
+
+/// `position turn=X moves=4 board=X.O/.X./... ...` for the board as seen by
+/// the side to move. Cells are `.` (empty), `#` (blocked), `X`, `O`, `T` or a
+/// digit; rows are separated by `/`.
+pub fn encode_position(state: &GameState) -> String {
+    let mut line = format!(
+        "position turn={} moves={} board={}",
+        state.turn.letter(),
+        state.moves,
+        encode_board(&state.board, Some(&state.blocked))
+    );
+    // Hidden marks have no age either, or it would give them away
+    let ages: Vec<String> = state
+        .placed_at
+        .iter()
+        .map(|(pos, age)| match state.board[pos] {
+            Some(_) => age.to_string(),
+            None => "0".to_string(),
+        })
+        .collect();
+    line.push_str(&format!(" ages={}", ages.join(",")));
+    if !state.boards.is_empty() {
+        let layers: Vec<String> = state.boards.iter().map(|b| encode_board(b, None)).collect();
+        line.push_str(&format!(" layers={}", layers.join("|")));
+    }
+    if state.rules.fog {
+        let revealed: String = state
+            .revealed
+            .iter()
+            .map(|(_, &r)| if r { '1' } else { '0' })
+            .collect();
+        line.push_str(&format!(" revealed={revealed}"));
+    }
+    let spooky: Vec<String> = state
+        .spooky
+        .iter()
+        .flat_map(|((row, col), marks)| {
+            marks.iter().map(move |mark| {
+                format!("{row}.{col}.{}.{}", mark.player.letter(), mark.move_number)
+            })
+        })
+        .collect();
+    if !spooky.is_empty() {
+        line.push_str(&format!(" spooky={}", spooky.join(",")));
+    }
+    if let Some(pending) = state.pending_collapse {
+        let [(r1, c1), (r2, c2)] = pending.cells;
+        line.push_str(&format!(
+            " collapse={}.{}.{r1}.{c1}.{r2}.{c2}",
+            pending.mark.player.letter(),
+            pending.mark.move_number
+        ));
+    }
+    line
+}
+
+/// Rebuilds a position under `rules`; obstacles and setup marks come from
+/// the board, so no setup is applied.
+pub fn decode_position(rules: &GameRules, line: &str) -> Result<GameState, String> {
+    let mut state = GameState {
+        rules: GameRules {
+            setup: Setup::None,
+            ..rules.normalized()
+        },
+        ..Default::default()
+    };
+    state.reset();
+    let size = state.rules.board_size;
+    for (key, value) in fields(line, "position")? {
+        match key {
+            "turn" => state.turn = parse_player(single_char(value)?)?,
+            "moves" => state.moves = parse_number(value)?,
+            "board" => {
+                let (board, blocked) = decode_board(value, size)?;
+                state.board = board;
+                state.blocked = blocked;
+            }
+            "ages" => {
+                let ages: Vec<usize> = value
+                    .split(',')
+                    .map(parse_number)
+                    .collect::<Result<_, _>>()?;
+                fill(&mut state.placed_at, ages)?;
+            }
+            "layers" => {
+                let layers: Vec<Board<_>> = value
+                    .split('|')
+                    .map(|b| decode_board(b, size).map(|(board, _)| board))
+                    .collect::<Result<_, _>>()?;
+                if layers.len() != state.boards.len() {
+                    return Err(format!(
+                        "expected {} layers, got {}",
+                        state.boards.len(),
+                        layers.len()
+                    ));
+                }
+                state.boards = layers;
+            }
+            "revealed" => fill(
+                &mut state.revealed,
+                value.chars().map(|c| c == '1').collect(),
+            )?,
+            "spooky" => {
+                for mark in value.split(',') {
+                    let parts: Vec<&str> = mark.split('.').collect();
+                    let [row, col, player, number] = parts.as_slice() else {
+                        return Err(format!("bad spooky mark {mark}"));
+                    };
+                    let pos = (parse_number(row)?, parse_number(col)?);
+                    check_cell(pos, size)?;
+                    state.spooky[pos].push(SpookyMark {
+                        player: parse_player(single_char(player)?)?,
+                        move_number: parse_number(number)?,
+                    });
+                }
+            }
+            "collapse" => {
+                let parts: Vec<&str> = value.split('.').collect();
+                let [player, number, r1, c1, r2, c2] = parts.as_slice() else {
+                    return Err(format!("bad collapse {value}"));
+                };
+                let cells = [
+                    (parse_number(r1)?, parse_number(c1)?),
+                    (parse_number(r2)?, parse_number(c2)?),
+                ];
+                for cell in cells {
+                    check_cell(cell, size)?;
+                }
+                state.pending_collapse = Some(PendingCollapse {
+                    mark: SpookyMark {
+                        player: parse_player(single_char(player)?)?,
+                        move_number: parse_number(number)?,
+                    },
+                    cells,
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(state)
+}
+
+/// `place 1 1`, `place 0 2 O`, `slide 0 0 1 1`, `spooky 0 0 2 2`,
+/// `on 2 1 1` or `collapse 1 1`.
+pub fn encode_move(mv: GameMove) -> String {
+    match mv {
+        GameMove::Place((row, col)) => format!("place {row} {col}"),
+        GameMove::PlaceSymbol((row, col), symbol) => {
+            format!("place {row} {col} {}", symbol_char(Some(symbol)))
+        }
+        GameMove::Slide { from, to } => {
+            format!("slide {} {} {} {}", from.0, from.1, to.0, to.1)
+        }
+        GameMove::Spooky(a, b) => format!("spooky {} {} {} {}", a.0, a.1, b.0, b.1),
+        GameMove::PlaceOn(board, (row, col)) => format!("on {board} {row} {col}"),
+        GameMove::Collapse((row, col)) => format!("collapse {row} {col}"),
+    }
+}
+
+pub fn decode_move(text: &str) -> Result<GameMove, String> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let numbers = |from: usize, count: usize| -> Result<Vec<usize>, String> {
+        let values = tokens
+            .get(from..from + count)
+            .ok_or("missing coordinates")?;
+        values.iter().map(|t| parse_number(t)).collect()
+    };
+    let mv = match tokens.first().copied() {
+        Some("place") if tokens.len() == 4 => {
+            let n = numbers(1, 2)?;
+            let symbol = decode_symbol(single_char(tokens[3])?)?
+                .ok_or_else(|| format!("bad symbol {}", tokens[3]))?;
+            GameMove::PlaceSymbol((n[0], n[1]), symbol)
+        }
+        Some("place") => {
+            let n = numbers(1, 2)?;
+            GameMove::Place((n[0], n[1]))
+        }
+        Some("slide") => {
+            let n = numbers(1, 4)?;
+            GameMove::Slide {
+                from: (n[0], n[1]),
+                to: (n[2], n[3]),
+            }
+        }
+        Some("spooky") => {
+            let n = numbers(1, 4)?;
+            GameMove::Spooky((n[0], n[1]), (n[2], n[3]))
+        }
+        Some("on") => {
+            let n = numbers(1, 3)?;
+            GameMove::PlaceOn(n[0], (n[1], n[2]))
+        }
+        Some("collapse") => {
+            let n = numbers(1, 2)?;
+            GameMove::Collapse((n[0], n[1]))
+        }
+        _ => return Err(format!("unknown move {text}")),
+    };
+    Ok(mv)
+}
+
+fn encode_board(board: &Board<Option<Symbol>>, blocked: Option<&Board<bool>>) -> String {
+    let size = board.size();
+    let rows: Vec<String> = (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    if blocked.is_some_and(|b| b[(row, col)]) {
+                        '#'
+                    } else {
+                        symbol_char(board[(row, col)])
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    rows.join("/")
+}
+
+type DecodedBoard = (Board<Option<Symbol>>, Board<bool>);
+
+fn decode_board(text: &str, size: usize) -> Result<DecodedBoard, String> {
+    let mut board = Board::new(size);
+    let mut blocked = Board::new(size);
+    let rows: Vec<&str> = text.split('/').collect();
+    if rows.len() != size || rows.iter().any(|r| r.chars().count() != size) {
+        return Err(format!("board {text} is not {size}x{size}"));
+    }
+    for (row, cells) in rows.iter().enumerate() {
+        for (col, c) in cells.chars().enumerate() {
+            if c == '#' {
+                blocked[(row, col)] = true;
+            } else {
+                board[(row, col)] = decode_symbol(c)?;
+            }
+        }
+    }
+    Ok((board, blocked))
+}
+
+fn symbol_char(symbol: Option<Symbol>) -> char {
+    match symbol {
+        None => '.',
+        Some(Symbol::X) => 'X',
+        Some(Symbol::O) => 'O',
+        Some(Symbol::Triangle) => 'T',
+        Some(Symbol::Number(n)) => char::from(b'0' + n),
+    }
+}
+
+fn decode_symbol(c: char) -> Result<Option<Symbol>, String> {
+    Ok(match c {
+        '.' => None,
+        'X' => Some(Symbol::X),
+        'O' => Some(Symbol::O),
+        'T' => Some(Symbol::Triangle),
+        '1'..='9' => Some(Symbol::Number(c as u8 - b'0')),
+        _ => return Err(format!("bad cell {c}")),
+    })
+}
+
+fn parse_player(c: char) -> Result<Player, String> {
+    [Player::X, Player::O, Player::Triangle]
+        .into_iter()
+        .find(|p| p.letter().starts_with(c))
+        .ok_or_else(|| format!("bad player {c}"))
+}
+
+/// Modes are named as in the source, e.g. `OrderChaos`.
+fn parse_mode(name: &str) -> Result<GameMode, String> {
+    let mut mode = GameMode::default();
+    loop {
+        if format!("{mode:?}") == name {
+            return Ok(mode);
+        }
+        mode = mode.next();
+        if mode == GameMode::default() {
+            return Err(format!("unknown mode {name}"));
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("bad number {text}"))
+}
+
+fn single_char(text: &str) -> Result<char, String> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("expected one character, got {text}")),
+    }
+}
+
+/// Rejects board shapes no game can be set up on.
+fn check_shape(rules: &GameRules) -> Result<(), String> {
+    let (size, win) = (rules.board_size, rules.win_length);
+    if !(1..=MAX_BOARD_SIZE).contains(&size) {
+        Err(format!("board size {size} is out of range"))
+    } else if !(1..=MAX_BOARD_COUNT).contains(&rules.board_count) {
+        Err(format!("board count {} is out of range", rules.board_count))
+    } else if win == 0 || win > size {
+        Err(format!(
+            "win length {win} doesn't fit a board of size {size}"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_cell((row, col): (usize, usize), size: usize) -> Result<(), String> {
+    if row < size && col < size {
+        Ok(())
+    } else {
+        Err(format!("cell {row} {col} is off the board"))
+    }
+}
+
+/// Copies row-major `values` into `board`.
+fn fill<T: Clone + Default>(board: &mut Board<T>, values: Vec<T>) -> Result<(), String> {
+    let size = board.size();
+    if values.len() != size * size {
+        return Err(format!(
+            "expected {} cells, got {}",
+            size * size,
+            values.len()
+        ));
+    }
+    for (i, value) in values.into_iter().enumerate() {
+        board[(i / size, i % size)] = value;
+    }
+    Ok(())
+}
+
+/// The `key=value` tokens of a line starting with `command`.
+fn fields<'a>(line: &'a str, command: &str) -> Result<Vec<(&'a str, &'a str)>, String> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some(command) {
+        return Err(format!("expected {command}, got {line}"));
+    }
+    tokens
+        .map(|token| {
+            token
+                .split_once('=')
+                .ok_or_else(|| format!("bad field {token}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::fog;
+    use crate::game::systems::make_move;
+
+    fn rules(line: &str) -> GameRules {
+        decode_rules(line).expect("valid rules").normalized()
+    }
+
+    #[test]
+    fn rules_round_trip() {
+        let rules = rules("rules mode=Classic size=5 win=4 gravity=1 wrap=1 players=XTO fog=0");
+        assert_eq!(decode_rules(&encode_rules(&rules)), Ok(rules));
+    }
+
+    #[test]
+    fn rejects_boards_that_cannot_be_set_up() {
+        assert!(decode_rules("rules mode=Classic size=0 win=3").is_err());
+        assert!(decode_rules("rules mode=Classic size=3 win=4").is_err());
+        assert!(decode_rules("rules mode=Classic size=3 win=0").is_err());
+        assert!(decode_rules("rules mode=Classic size=99 win=3").is_err());
+        assert!(decode_rules("rules mode=Notakto size=3 win=3 boards=0").is_err());
+        assert!(decode_rules("rules mode=Notakto size=3 win=3 boards=4").is_err());
+    }
+
+    #[test]
+    fn position_round_trip() {
+        let rules = rules("rules mode=Vanishing size=4 win=3");
+        let mut state = GameState {
+            rules,
+            ..Default::default()
+        };
+        state.reset();
+        for mv in [(0, 0), (1, 1), (2, 0), (3, 3)] {
+            make_move(&mut state, GameMove::Place(mv));
+        }
+        let decoded = decode_position(&rules, &encode_position(&state)).expect("valid position");
+        assert_eq!(decoded.board, state.board);
+        assert_eq!(decoded.placed_at, state.placed_at);
+        assert_eq!(decoded.turn, state.turn);
+        assert_eq!(decoded.moves, state.moves);
+    }
+
+    #[test]
+    fn moves_round_trip() {
+        for mv in [
+            GameMove::Place((2, 1)),
+            GameMove::Slide {
+                from: (0, 0),
+                to: (1, 1),
+            },
+            GameMove::PlaceSymbol((1, 2), Symbol::O),
+            GameMove::PlaceSymbol((0, 0), Symbol::Number(7)),
+            GameMove::Spooky((0, 1), (2, 2)),
+            GameMove::PlaceOn(2, (1, 0)),
+            GameMove::Collapse((1, 1)),
+        ] {
+            assert_eq!(decode_move(&encode_move(mv)), Ok(mv));
+        }
+    }
+
+    #[test]
+    fn hidden_marks_have_no_age() {
+        let rules = rules("rules mode=Classic size=3 win=3 fog=1");
+        let mut state = GameState {
+            rules,
+            ..Default::default()
+        };
+        state.reset();
+        make_move(&mut state, GameMove::Place((1, 1)));
+        let line = encode_position(&fog::view_for(&state, Player::O));
+        assert!(line.contains("board=.../.../..."), "{line}");
+        assert!(line.contains("ages=0,0,0,0,0,0,0,0,0"), "{line}");
+    }
+
+    #[test]
+    fn rejects_layers_that_dont_match_the_rules() {
+        let notakto = rules("rules mode=Notakto size=3 win=3 boards=2");
+        let two = "position turn=X moves=0 layers=.../.../...|.../.../...";
+        assert!(decode_position(&notakto, two).is_ok());
+        assert!(decode_position(&notakto, "position turn=X moves=0 layers=.../.../...").is_err());
+        let qubic = rules("rules mode=Qubic size=3 win=3");
+        assert!(decode_position(&qubic, two).is_err());
+    }
+}
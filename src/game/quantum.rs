@@ -0,0 +1,230 @@
+use std::collections::VecDeque;
+
+use super::state::{GameMove, GameState, Player, WinningLine};
+
+/// One half of an entangled pair, subscripted with the move that placed it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SpookyMark {
+    pub player: Player,
+    /// Zero-based move number; displayed as `move + 1`.
+    pub move_number: usize,
+}
+
+/// A cycle waiting for the other player to choose where `mark` collapses.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PendingCollapse {
+    pub mark: SpookyMark,
+    pub cells: [(usize, usize); 2],
+}
+
+/// Cells without a classical mark.
+pub fn open_cells(state: &GameState) -> Vec<(usize, usize)> {
+    state
+        .board
+        .iter()
+        .filter(|(_, cell)| cell.is_none())
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+pub fn legal_moves(state: &GameState) -> Vec<GameMove> {
+    if let Some(pending) = state.pending_collapse {
+        return pending.cells.map(GameMove::Collapse).to_vec();
+    }
+
+    let open = open_cells(state);
+    // The last open cell can only take a classical mark
+    if open.len() == 1 {
+        return vec![GameMove::Place(open[0])];
+    }
+
+    let mut moves = Vec::new();
+    for (i, &a) in open.iter().enumerate() {
+        for &b in &open[i + 1..] {
+            moves.push(GameMove::Spooky(a, b));
+        }
+    }
+    moves
+}
+
+/// Places an entangled pair for the side to move. Closing a cycle hands the
+/// collapse choice to the opponent, who moves next.
+pub fn play_spooky(state: &mut GameState, a: (usize, usize), b: (usize, usize)) {
+    let mark = SpookyMark {
+        player: state.turn,
+        move_number: state.moves,
+    };
+    let cycle = are_entangled(state, a, b);
+
+    state.spooky[a].push(mark);
+    state.spooky[b].push(mark);
+    state.moves += 1;
+    state.last_move = None;
+    state.turn = state.turn.other();
+
+    if cycle {
+        state.pending_collapse = Some(PendingCollapse {
+            mark,
+            cells: [a, b],
+        });
+    }
+}
+
+/// Collapses the pending cycle with its newest mark landing in `cell`, then
+/// scores any completed lines. The chooser keeps the turn.
+pub fn resolve_collapse(state: &mut GameState, cell: (usize, usize)) {
+    let Some(pending) = state.pending_collapse.take() else {
+        return;
+    };
+    collapse(state, pending.mark, cell);
+    score_lines(state);
+
+    if state.winner.is_none() && open_cells(state).is_empty() {
+        state.draw = true;
+    }
+}
+
+/// Whether `a` and `b` are already connected through entangled pairs.
+fn are_entangled(state: &GameState, a: (usize, usize), b: (usize, usize)) -> bool {
+    let mut seen = vec![a];
+    let mut queue = VecDeque::from([a]);
+    while let Some(cell) = queue.pop_front() {
+        if cell == b {
+            return true;
+        }
+        for &mark in &state.spooky[cell] {
+            if let Some(next) = partner_cell(state, mark, cell) {
+                if !seen.contains(&next) {
+                    seen.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    false
+}
+
+/// The other cell holding the entangled twin of `mark`.
+fn partner_cell(
+    state: &GameState,
+    mark: SpookyMark,
+    cell: (usize, usize),
+) -> Option<(usize, usize)> {
+    state
+        .spooky
+        .iter()
+        .find(|(pos, marks)| *pos != cell && marks.contains(&mark))
+        .map(|(pos, _)| pos)
+}
+
+/// Makes `mark` classical in `cell`; every other spooky mark there is forced
+/// into its partner cell, and so on through the entanglement graph.
+fn collapse(state: &mut GameState, mark: SpookyMark, cell: (usize, usize)) {
+    let mut stack = vec![(mark, cell)];
+    while let Some((mark, cell)) = stack.pop() {
+        if state.board[cell].is_some() {
+            continue;
+        }
+        let others = std::mem::take(&mut state.spooky[cell]);
+        state.board[cell] = Some(mark.player.symbol());
+        state.placed_at[cell] = mark.move_number;
+
+        // The twin of the collapsed mark disappears
+        if let Some(twin) = partner_cell(state, mark, cell) {
+            state.spooky[twin].retain(|&m| m != mark);
+        }
+
+        for other in others.into_iter().filter(|&m| m != mark) {
+            if let Some(next) = partner_cell(state, other, cell) {
+                stack.push((other, next));
+            }
+        }
+    }
+}
+
+/// Scores classical lines after a collapse. If both players complete a line
+/// at once, the one whose line was finished earlier (lower highest subscript)
+/// wins and the other earns half a point.
+fn score_lines(state: &mut GameState) {
+    let mut best: [Option<(usize, WinningLine)>; 2] = [None, None];
+    for (player, line, finished) in completed_lines(state) {
+        let slot = &mut best[player as usize];
+        if slot.as_ref().is_none_or(|(prev, _)| finished < *prev) {
+            *slot = Some((finished, line));
+        }
+    }
+
+    let (winner, runner_up) = match best {
+        [Some(x), Some(o)] if x.0 < o.0 => (Some((Player::X, x.1)), Some(Player::O)),
+        [Some(_), Some(o)] => (Some((Player::O, o.1)), Some(Player::X)),
+        [Some(x), None] => (Some((Player::X, x.1)), None),
+        [None, Some(o)] => (Some((Player::O, o.1)), None),
+        [None, None] => (None, None),
+    };
+
+    if let Some((player, line)) = winner {
+        state.winner = Some(player);
+        state.winning_line = Some(line);
+        state.runner_up = runner_up;
+    }
+}
+
+/// Every complete classical line with its owner and the highest move number in it.
+fn completed_lines(state: &GameState) -> Vec<(Player, WinningLine, usize)> {
+    let mut lines = Vec::new();
+    for line in state.lines.iter() {
+        let Some(symbol) = state.symbol_at(line[0]) else {
+            continue;
+        };
+        if line
+            .iter()
+            .all(|&cell| state.symbol_at(cell) == Some(symbol))
+        {
+            let finished = line
+                .iter()
+                .map(|&(_, row, col)| state.placed_at[(row, col)])
+                .max();
+            lines.push((
+                symbol.owner(),
+                WinningLine::spanning(line),
+                finished.unwrap_or(0),
+            ));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::{GameMode, GameRules};
+    use crate::game::systems::make_move;
+
+    #[test]
+    fn a_cycle_collapses_where_the_opponent_chooses() {
+        let mut state = GameState {
+            rules: GameRules {
+                mode: GameMode::Quantum,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        make_move(&mut state, GameMove::Spooky((0, 0), (0, 1)));
+        assert_eq!(state.pending_collapse, None);
+        make_move(&mut state, GameMove::Spooky((0, 0), (0, 1)));
+
+        // O closed the cycle, so X picks where O's newest mark lands
+        assert_eq!(state.turn, Player::X);
+        assert_eq!(
+            state.legal_moves(),
+            vec![GameMove::Collapse((0, 0)), GameMove::Collapse((0, 1))]
+        );
+        make_move(&mut state, GameMove::Collapse((0, 0)));
+        assert_eq!(state.board[(0, 0)], Some(Player::O.symbol()));
+        assert_eq!(state.board[(0, 1)], Some(Player::X.symbol()));
+        assert_eq!(state.turn, Player::X);
+        assert!(state.spooky[(0, 0)].is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use rand::seq::SliceRandom;
+
+use super::lines::check_winner;
+use super::state::{GameMove, GameState};
+
+pub fn legal_moves(state: &GameState) -> Vec<GameMove> {
+    state
+        .boards
+        .iter()
+        .enumerate()
+        .flat_map(|(layer, board)| {
+            board
+                .iter()
+                .filter(|(_, cell)| cell.is_none())
+                .map(move |(pos, _)| GameMove::PlaceOn(layer, pos))
+        })
+        .collect()
+}
+
+/// Places the mover's mark on a layer; any of the cube's lines wins.
+pub fn play(state: &mut GameState, layer: usize, pos: (usize, usize)) {
+    let player = state.turn;
+    state.boards[layer][pos] = Some(player.symbol());
+    state.moves += 1;
+
+    if let Some(line) = check_winner(state) {
+        state.winner = Some(player);
+        state.winning_line = Some(line);
+        return;
+    }
+
+    state.turn = player.other();
+    if legal_moves(state).is_empty() {
+        state.draw = true;
+    }
+}
+
+/// Line-counting heuristic: win, block, fork (two open threes at once), stop
+/// the opponent's fork, then the cell on the most promising open lines.
+pub fn best_move(state: &GameState) -> Option<GameMove> {
+    line_move(state, legal_moves(state))
+}
+
+/// How the line-counting heuristic rates a move; better moves compare
+/// greater.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub struct LineScore {
+    wins: bool,
+    blocks: bool,
+    forks: bool,
+    blocks_fork: bool,
+    /// Worth of the open lines through the cell.
+    pub weight: u32,
+}
+
+/// The line-counting heuristic over `moves`; it also suits flat boards,
+/// where each move places a mark on one cell of layer 0.
+pub fn line_move(state: &GameState, mut moves: Vec<GameMove>) -> Option<GameMove> {
+    moves.shuffle(&mut state.rng());
+    let scores = line_scores(state, &moves);
+    moves
+        .into_iter()
+        .zip(scores)
+        .max_by_key(|&(_, score)| score)
+        .map(|(mv, _)| mv)
+}
+
+/// The heuristic's rating of each of `moves`; moves other than placing a
+/// mark rate lowest.
+pub fn line_scores(state: &GameState, moves: &[GameMove]) -> Vec<LineScore> {
+    let me = state.turn;
+    let n = state.rules.win_length;
+
+    // (own marks, opponent marks) on every line
+    let counts: Vec<(usize, usize)> = state
+        .lines
+        .iter()
+        .map(|line| {
+            line.iter().fold((0, 0), |(mine, theirs), &cell| {
+                match state.symbol_at(cell) {
+                    Some(symbol) if symbol.owner() == me => (mine + 1, theirs),
+                    Some(_) => (mine, theirs + 1),
+                    None => (mine, theirs),
+                }
+            })
+        })
+        .collect();
+
+    moves
+        .iter()
+        .map(|&mv| {
+            let (layer, (row, col)) = match mv {
+                GameMove::PlaceOn(layer, pos) => (layer, pos),
+                GameMove::Place(pos) => (0, pos),
+                _ => return LineScore::default(),
+            };
+            let through: Vec<(usize, usize)> = state
+                .lines
+                .iter()
+                .zip(&counts)
+                .filter(|(line, _)| line.contains(&(layer, row, col)))
+                .map(|(_, &count)| count)
+                .collect();
+            let lines_with = |count| through.iter().filter(|&&c| c == count).count();
+
+            // Open lines are worth more the fuller they are; blocking slightly less
+            let weight: u32 = through
+                .iter()
+                .map(|&(mine, theirs)| match (mine, theirs) {
+                    (mine, 0) => 4u32.pow(mine as u32),
+                    (0, theirs) => 3 * 4u32.pow(theirs as u32) / 4,
+                    _ => 0,
+                })
+                .sum();
+            LineScore {
+                wins: lines_with((n - 1, 0)) > 0,
+                blocks: lines_with((0, n - 1)) > 0,
+                forks: lines_with((n - 2, 0)) >= 2,
+                blocks_fork: lines_with((0, n - 2)) >= 2,
+                weight,
+            }
+        })
+        .collect()
+}
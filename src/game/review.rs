@@ -0,0 +1,217 @@
+//! Post-game review: replays a finished game and grades every move against
+//! the solver's best, so mistakes can be stepped through on the board.
+
+use std::sync::atomic::AtomicBool;
+
+use super::solver::{Outcome, Solver};
+use super::state::{GameMove, GameState, Player, Symbol};
+use super::systems::make_move;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Verdict {
+    /// Keeps the best result reachable, as quickly as possible.
+    Best,
+    /// Keeps the result but wins more slowly or loses sooner.
+    Inaccuracy,
+    /// Throws away a result: a win into a draw, or a draw into a loss.
+    Blunder,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MoveReview {
+    pub player: Player,
+    /// Best result the player could reach; `None` when the position was too
+    /// big to solve.
+    pub best: Option<Outcome>,
+    /// Result the move played leads to with perfect play.
+    pub played: Option<Outcome>,
+}
+
+impl MoveReview {
+    pub fn verdict(&self) -> Option<Verdict> {
+        let (best, played) = (self.best?, self.played?);
+        let result = |outcome: Outcome| match outcome {
+            Outcome::Win(_) => 1,
+            Outcome::Draw(_) => 0,
+            Outcome::Loss(_) => -1,
+        };
+        Some(if result(played) < result(best) {
+            Verdict::Blunder
+        } else if played.rank() < best.rank() {
+            Verdict::Inaccuracy
+        } else {
+            Verdict::Best
+        })
+    }
+
+    /// Such as "best move" or "blunder: turned a win into a draw".
+    pub fn label(&self) -> String {
+        match (self.verdict(), self.best, self.played) {
+            (Some(Verdict::Blunder), Some(best), Some(played)) => {
+                format!("blunder: turned a {} into a {}", best.name(), played.name())
+            }
+            (Some(Verdict::Inaccuracy), _, Some(Outcome::Win(_))) => {
+                "inaccuracy: a quicker win was possible".to_string()
+            }
+            (Some(Verdict::Inaccuracy), _, _) => "inaccuracy: the loss comes sooner".to_string(),
+            (Some(_), _, _) => "best move".to_string(),
+            (None, _, _) => "too many positions to solve".to_string(),
+        }
+    }
+}
+
+/// The game as it stood after its first `count` moves; `None` before the
+/// first move.
+pub fn replay(state: &GameState, count: usize) -> Option<GameState> {
+    let mut position = state.opening.as_deref()?.clone();
+    for &mv in state.history.iter().take(count) {
+        make_move(&mut position, mv);
+    }
+    Some(position)
+}
+
+/// Grades every move of the game. Stops solving once a position is too
+/// big, leaving the earlier moves ungraded; `cancel` stops it early.
+pub fn review(state: &GameState, cancel: &AtomicBool) -> Vec<MoveReview> {
+    let Some(mut position) = state.opening.as_deref().cloned() else {
+        return Vec::new();
+    };
+    let mut positions = Vec::new();
+    for &mv in &state.history {
+        positions.push((position.clone(), mv));
+        make_move(&mut position, mv);
+    }
+
+    // Latest moves first: they are quickest to solve and their positions
+    // are shared with the earlier ones
+    let mut solver = Solver::new(cancel);
+    let mut solvable = true;
+    let mut reviews = Vec::new();
+    for (before, mv) in positions.into_iter().rev() {
+        let mut after = before.clone();
+        make_move(&mut after, mv);
+        let (mut best, mut played) = (None, None);
+        if solvable {
+            match (solver.solve(&before), solver.solve(&after)) {
+                (Some(b), Some(p)) => {
+                    best = Some(b);
+                    played = Some(p.before_move(after.turn == before.turn));
+                }
+                _ => solvable = false,
+            }
+        }
+        reviews.push(MoveReview {
+            player: before.turn,
+            best,
+            played,
+        });
+    }
+    reviews.reverse();
+    reviews
+}
+
+/// Share of `player`'s graded moves that were best, counting inaccuracies
+/// as half; `None` when none of their moves could be graded.
+pub fn accuracy(reviews: &[MoveReview], player: Player) -> Option<u32> {
+    let verdicts: Vec<_> = reviews
+        .iter()
+        .filter(|review| review.player == player)
+        .filter_map(MoveReview::verdict)
+        .collect();
+    if verdicts.is_empty() {
+        return None;
+    }
+    let score: f32 = verdicts
+        .iter()
+        .map(|verdict| match verdict {
+            Verdict::Best => 1.0,
+            Verdict::Inaccuracy => 0.5,
+            Verdict::Blunder => 0.0,
+        })
+        .sum();
+    Some((100.0 * score / verdicts.len() as f32).round() as u32)
+}
+
+/// Move in board coordinates: columns a, b, c... from the left and rows 1,
+/// 2, 3... from the bottom, such as "b2" or "b2-c3".
+pub fn describe_move(mv: GameMove) -> String {
+    let cell = |(row, col): (usize, usize)| format!("{}{}", (b'a' + col as u8) as char, row + 1);
+    match mv {
+        GameMove::Place(pos) => cell(pos),
+        GameMove::Slide { from, to } => format!("{}-{}", cell(from), cell(to)),
+        GameMove::PlaceSymbol(pos, Symbol::Number(n)) => format!("{n} at {}", cell(pos)),
+        GameMove::PlaceSymbol(pos, symbol) => format!("{symbol:?} at {}", cell(pos)),
+        GameMove::Spooky(a, b) => format!("{}+{}", cell(a), cell(b)),
+        GameMove::PlaceOn(board, pos) => format!("board {} {}", board + 1, cell(pos)),
+        GameMove::Collapse(pos) => format!("collapse {}", cell(pos)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::GameRules;
+
+    fn graded(best: Outcome, played: Outcome) -> MoveReview {
+        MoveReview {
+            player: Player::X,
+            best: Some(best),
+            played: Some(played),
+        }
+    }
+
+    #[test]
+    fn verdicts() {
+        use Outcome::*;
+        assert_eq!(graded(Win(3), Win(3)).verdict(), Some(Verdict::Best));
+        assert_eq!(graded(Win(3), Win(5)).verdict(), Some(Verdict::Inaccuracy));
+        assert_eq!(
+            graded(Loss(6), Loss(2)).verdict(),
+            Some(Verdict::Inaccuracy)
+        );
+        assert_eq!(graded(Win(3), Draw(8)).verdict(), Some(Verdict::Blunder));
+        assert_eq!(graded(Draw(8), Loss(4)).verdict(), Some(Verdict::Blunder));
+        assert_eq!(
+            graded(Win(3), Draw(8)).label(),
+            "blunder: turned a win into a draw"
+        );
+
+        let unsolved = MoveReview {
+            player: Player::X,
+            best: None,
+            played: None,
+        };
+        assert_eq!(unsolved.verdict(), None);
+    }
+
+    #[test]
+    fn accuracy_counts_inaccuracies_as_half() {
+        use Outcome::*;
+        let reviews = [
+            graded(Draw(9), Draw(9)),
+            graded(Win(5), Win(7)),
+            graded(Draw(5), Loss(2)),
+            graded(Draw(3), Draw(3)),
+        ];
+        assert_eq!(accuracy(&reviews, Player::X), Some(63));
+        assert_eq!(accuracy(&reviews, Player::O), None);
+    }
+
+    #[test]
+    fn an_edge_reply_to_the_centre_is_a_blunder() {
+        let mut state = GameState {
+            rules: GameRules::default().normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        for pos in [(1, 1), (0, 1)] {
+            make_move(&mut state, GameMove::Place(pos));
+        }
+        let reviews = review(&state, &AtomicBool::new(false));
+        assert_eq!(reviews.len(), 2);
+        assert_eq!(reviews[0].verdict(), Some(Verdict::Best));
+        assert_eq!(reviews[1].player, Player::O);
+        assert_eq!(reviews[1].verdict(), Some(Verdict::Blunder));
+        assert_eq!(describe_move(GameMove::Place((0, 1))), "b1");
+    }
+}
@@ -0,0 +1,131 @@
+use rand::seq::SliceRandom;
+
+use super::fog;
+use super::lines::check_winner;
+use super::state::{GameState, Setup, Symbol};
+
+/// Places the obstacles and handicap marks of the selected preset on a
+/// freshly reset board. A custom setup starts the setup phase instead,
+/// pre-filled with the previous custom arrangement.
+pub fn apply(state: &mut GameState) {
+    let last = state.board.size() - 1;
+    let centre = (last / 2, last / 2);
+    match state.rules.setup {
+        Setup::None => {}
+        Setup::BlockedCentre => state.blocked[centre] = true,
+        Setup::BlockedCorners => {
+            for pos in [(0, 0), (0, last), (last, 0), (last, last)] {
+                state.blocked[pos] = true;
+            }
+        }
+        Setup::RandomObstacles => {
+            let cells: Vec<(usize, usize)> = state.board.iter().map(|(pos, _)| pos).collect();
+            for &pos in cells.choose_multiple(&mut state.rng(), last) {
+                state.blocked[pos] = true;
+            }
+        }
+        Setup::CentreForX => state.board[centre] = Some(Symbol::X),
+        Setup::CentreForO => state.board[centre] = Some(Symbol::O),
+        Setup::Custom => {
+            if let Some((board, blocked)) = state.custom_setup.clone() {
+                state.board = board;
+                state.blocked = blocked;
+            }
+            state.setup_phase = true;
+        }
+    }
+}
+
+/// Cycles a cell during the setup phase: empty, blocked, X, O, empty.
+pub fn cycle_cell(state: &mut GameState, pos: (usize, usize)) {
+    let (mark, blocked) = match (state.board[pos], state.blocked[pos]) {
+        (None, false) => (None, true),
+        (None, true) => (Some(Symbol::X), false),
+        (Some(Symbol::X), _) => (Some(Symbol::O), false),
+        _ => (None, false),
+    };
+    state.board[pos] = mark;
+    state.blocked[pos] = blocked;
+    state.changes += 1;
+}
+
+/// Why the arrangement can't start a game, if it can't: the game would be
+/// over already, or gravity couldn't have left the marks where they are.
+pub fn problem(state: &GameState) -> Option<&'static str> {
+    if check_winner(state).is_some() {
+        return Some("a line is already complete");
+    }
+    if state.legal_moves().is_empty() {
+        return Some("no move is left to play");
+    }
+    if state.rules.gravity && floating(state) {
+        return Some(
+            "with gravity, marks and obstacles must rest on the bottom row or on each other",
+        );
+    }
+    None
+}
+
+/// Whether a mark or obstacle sits above an empty cell.
+fn floating(state: &GameState) -> bool {
+    let size = state.board.size();
+    (0..size)
+        .any(|col| (1..size).any(|row| !state.is_open((row, col)) && state.is_open((row - 1, col))))
+}
+
+/// Ends the setup phase, unless the arrangement has a `problem`; it is
+/// remembered for the next game.
+pub fn finish(state: &mut GameState) -> Result<(), &'static str> {
+    if let Some(problem) = problem(state) {
+        return Err(problem);
+    }
+    state.setup_phase = false;
+    state.changes += 1;
+    state.custom_setup = Some((state.board.clone(), state.blocked.clone()));
+    fog::reveal_marks(state);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::GameRules;
+
+    fn custom(gravity: bool) -> GameState {
+        let mut state = GameState {
+            rules: GameRules {
+                setup: Setup::Custom,
+                gravity,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        state
+    }
+
+    #[test]
+    fn a_finished_game_cannot_start() {
+        let mut state = custom(false);
+        for col in 0..3 {
+            state.board[(0, col)] = Some(Symbol::X);
+        }
+        assert!(finish(&mut state).is_err());
+        assert!(state.setup_phase);
+
+        state.board[(0, 1)] = None;
+        assert_eq!(finish(&mut state), Ok(()));
+        assert!(!state.setup_phase);
+    }
+
+    #[test]
+    fn gravity_setups_must_rest_on_the_bottom() {
+        let mut state = custom(true);
+        state.blocked[(1, 1)] = true;
+        assert!(problem(&state).is_some());
+
+        state.board[(0, 1)] = Some(Symbol::O);
+        assert_eq!(problem(&state), None);
+    }
+}
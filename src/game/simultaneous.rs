@@ -0,0 +1,203 @@
+use rand::Rng;
+
+use super::state::{GameMove, GameState, Player, WinningLine};
+
+/// Fictitious-play rounds used to approximate the computer's mixed strategy.
+const PLAY_ROUNDS: usize = 400;
+
+/// Secret cell choices in Simultaneous mode. Picks are committed one player
+/// at a time, with `GameState::turn` naming whoever is still choosing, and
+/// are only revealed together once everyone has picked.
+#[derive(Clone, Default, Debug)]
+pub struct SecretRound {
+    pub picks: Vec<(Player, (usize, usize))>,
+    /// Picks of the last revealed round.
+    pub last_reveal: Vec<(Player, (usize, usize))>,
+}
+
+impl SecretRound {
+    /// Whether two players picked the same cell in the last round.
+    pub fn last_collided(&self) -> bool {
+        self.last_reveal
+            .iter()
+            .enumerate()
+            .any(|(i, (_, pos))| self.last_reveal[i + 1..].iter().any(|(_, p)| p == pos))
+    }
+}
+
+/// Records the chooser's secret pick; the round is revealed once the last
+/// player has picked. Humans sharing the screen hand it over behind the
+/// curtain, so nobody sees where the others clicked.
+pub fn commit(state: &mut GameState, pos: (usize, usize)) {
+    let player = state.turn;
+    state.round.picks.push((player, pos));
+    let next = state.next_player(player);
+    if next == state.players()[0] {
+        let picks = std::mem::take(&mut state.round.picks);
+        reveal(state, picks);
+    } else {
+        state.turn = next;
+    }
+    state.curtain = true;
+}
+
+/// Places every pick at once. Cells picked by more than one player become
+/// blocked; a line for a single player wins and lines for several draw.
+fn reveal(state: &mut GameState, picks: Vec<(Player, (usize, usize))>) {
+    for &(player, pos) in &picks {
+        if picks.iter().filter(|(_, p)| *p == pos).count() > 1 {
+            state.blocked[pos] = true;
+        } else {
+            state.board[pos] = Some(player.symbol());
+            state.placed_at[pos] = state.moves;
+        }
+    }
+    state.round.last_reveal = picks;
+    state.moves += 1;
+    state.turn = state.players()[0];
+
+    let mut completed = state
+        .players()
+        .into_iter()
+        .filter_map(|player| completed_line(state, player).map(|line| (player, line)));
+    match (completed.next(), completed.next()) {
+        (Some((player, line)), None) => {
+            state.winner = Some(player);
+            state.winning_line = Some(line);
+        }
+        (Some(_), Some(_)) => state.draw = true,
+        _ => state.draw = state.is_full(),
+    }
+}
+
+fn completed_line(state: &GameState, player: Player) -> Option<WinningLine> {
+    state
+        .lines
+        .iter()
+        .find(|line| {
+            line.iter()
+                .all(|&cell| state.symbol_at(cell) == Some(player.symbol()))
+        })
+        .map(|line| WinningLine::spanning(line))
+}
+
+/// Treats the round as a zero-sum game over the open cells, approximates
+/// its equilibrium by fictitious play and samples a cell from the resulting
+/// mixed strategy, so the pick can't be predicted and exploited.
+pub fn best_move(state: &GameState) -> Option<GameMove> {
+    let me = state.turn;
+    let opponent = state.next_player(me);
+    let cells: Vec<(usize, usize)> = state
+        .board
+        .iter()
+        .map(|(pos, _)| pos)
+        .filter(|&pos| state.is_open(pos))
+        .collect();
+    if cells.is_empty() {
+        return None;
+    }
+
+    // Plan on the board alone: the opponent's pick this round is secret
+    let mut base = state.clone();
+    base.round.picks.clear();
+    let payoff: Vec<Vec<i64>> = cells
+        .iter()
+        .map(|&mine| {
+            cells
+                .iter()
+                .map(|&theirs| {
+                    let mut after = base.clone();
+                    reveal(&mut after, vec![(me, mine), (opponent, theirs)]);
+                    evaluate(&after, me)
+                })
+                .collect()
+        })
+        .collect();
+
+    // Each side repeatedly best-responds to the other's pick frequencies
+    let n = cells.len();
+    let mut my_counts = vec![0; n];
+    let mut my_totals = vec![0; n];
+    let mut their_totals = vec![0; n];
+    let (mut mine, mut theirs) = (0, 0);
+    for _ in 0..PLAY_ROUNDS {
+        my_counts[mine] += 1;
+        for i in 0..n {
+            my_totals[i] += payoff[i][theirs];
+            their_totals[i] += payoff[mine][i];
+        }
+        mine = (0..n).max_by_key(|&i| my_totals[i]).unwrap_or(0);
+        theirs = (0..n).min_by_key(|&i| their_totals[i]).unwrap_or(0);
+    }
+
+    let mut roll = state.rng().gen_range(0..PLAY_ROUNDS);
+    let index = my_counts
+        .iter()
+        .position(|&count| {
+            let hit = roll < count;
+            roll = roll.saturating_sub(count);
+            hit
+        })
+        .unwrap_or(0);
+    Some(GameMove::Place(cells[index]))
+}
+
+/// Outcome for `me` after a revealed round, or a line-counting score:
+/// lines only `me` occupies count for, lines only others occupy against.
+fn evaluate(state: &GameState, me: Player) -> i64 {
+    match state.winner {
+        Some(player) if player == me => return 1000,
+        Some(_) => return -1000,
+        None if state.draw => return 0,
+        None => {}
+    }
+    state
+        .lines
+        .iter()
+        .filter(|line| line.iter().all(|&(_, row, col)| !state.blocked[(row, col)]))
+        .map(|line| {
+            let (mine, theirs) = line.iter().fold((0, 0), |(mine, theirs), &cell| {
+                match state.symbol_at(cell) {
+                    Some(symbol) if symbol.owner() == me => (mine + 1, theirs),
+                    Some(_) => (mine, theirs + 1),
+                    None => (mine, theirs),
+                }
+            });
+            match (mine, theirs) {
+                (m, 0) => m * m,
+                (0, t) => -t * t,
+                _ => 0,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::{GameMode, GameRules};
+
+    #[test]
+    fn picks_stay_secret_until_both_are_in() {
+        let mut state = GameState {
+            rules: GameRules {
+                mode: GameMode::Simultaneous,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+
+        commit(&mut state, (1, 1));
+        assert_eq!(state.turn, Player::O);
+        assert!(state.curtain);
+        assert_eq!(state.board[(1, 1)], None);
+
+        state.curtain = false;
+        commit(&mut state, (1, 1));
+        assert!(state.blocked[(1, 1)]);
+        assert!(state.round.last_collided());
+        assert_eq!(state.turn, Player::X);
+    }
+}
@@ -0,0 +1,294 @@
+//! Exact game-theoretic values by exhaustive search, for the analysis
+//! overlay. Positions are memoized; the search gives up once it has seen
+//! too many, so big open boards only become solvable as they fill up.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::board::Board;
+use super::state::{GameMode, GameMove, GameState, Player, Symbol};
+use super::strategy::StrategyMove;
+use super::systems::make_move;
+
+/// Distinct positions the solver may visit before giving up.
+const MAX_POSITIONS: usize = 150_000;
+
+/// Result with perfect play for the side to move, and how many plies
+/// (single moves) until it happens.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Win(u32),
+    Draw(u32),
+    Loss(u32),
+}
+
+impl Outcome {
+    /// Higher is better for the side to move: quick wins, then draws, then
+    /// slow losses.
+    pub fn rank(self) -> i64 {
+        match self {
+            Outcome::Win(plies) => 1000 - plies as i64,
+            Outcome::Draw(_) => 0,
+            Outcome::Loss(plies) => plies as i64 - 1000,
+        }
+    }
+
+    /// The same result seen from the other side, one ply earlier.
+    pub fn before_move(self, same_side: bool) -> Self {
+        let outcome = if same_side {
+            self
+        } else {
+            match self {
+                Outcome::Win(plies) => Outcome::Loss(plies),
+                Outcome::Draw(plies) => Outcome::Draw(plies),
+                Outcome::Loss(plies) => Outcome::Win(plies),
+            }
+        };
+        match outcome {
+            Outcome::Win(plies) => Outcome::Win(plies + 1),
+            Outcome::Draw(plies) => Outcome::Draw(plies + 1),
+            Outcome::Loss(plies) => Outcome::Loss(plies + 1),
+        }
+    }
+
+    /// "win", "draw" or "loss", ignoring how far off it is.
+    pub fn name(self) -> &'static str {
+        match self {
+            Outcome::Win(_) => "win",
+            Outcome::Draw(_) => "draw",
+            Outcome::Loss(_) => "loss",
+        }
+    }
+
+    /// Short cell label such as "W3" (win in three plies) or "D".
+    pub fn label(self) -> String {
+        match self {
+            Outcome::Win(plies) => format!("W{plies}"),
+            Outcome::Draw(_) => "D".to_string(),
+            Outcome::Loss(plies) => format!("L{plies}"),
+        }
+    }
+}
+
+/// The value of playing on one cell; `board` is the Notakto board or Qubic
+/// layer, otherwise 0.
+#[derive(Clone, Copy, Debug)]
+pub struct CellValue {
+    pub board: usize,
+    pub cell: (usize, usize),
+    pub outcome: Outcome,
+}
+
+/// Whether positions can be solved: two players taking turns, each move
+/// putting a mark on a cell, and nothing hidden.
+pub fn supports(state: &GameState) -> bool {
+    matches!(
+        state.rules.mode,
+        GameMode::Classic
+            | GameMode::Wild
+            | GameMode::Notakto
+            | GameMode::Numerical
+            | GameMode::OrderChaos
+            | GameMode::Qubic
+    ) && !state.rules.three_players
+        && !state.rules.fog
+}
+
+/// The best move for the side to move, evaluated 1 when it wins, 0 when it
+/// draws and -1 when it loses. `None` for positions the solver doesn't
+/// support or can't finish within `limit` positions.
+pub fn best_move(state: &GameState, cancel: &AtomicBool, limit: usize) -> Option<StrategyMove> {
+    if !supports(state) {
+        return None;
+    }
+    let mut solver = Solver::with_limit(cancel, limit);
+    let mut best: Option<(GameMove, Outcome)> = None;
+    for mv in state.legal_moves() {
+        let mut after = state.clone();
+        make_move(&mut after, mv);
+        let outcome = solver.solve(&after)?.before_move(after.turn == state.turn);
+        if best.is_none_or(|(_, value)| outcome.rank() > value.rank()) {
+            best = Some((mv, outcome));
+        }
+    }
+    let (mv, outcome) = best?;
+    let evaluation = match outcome {
+        Outcome::Win(_) => 1.0,
+        Outcome::Draw(_) => 0.0,
+        Outcome::Loss(_) => -1.0,
+    };
+    Some(StrategyMove {
+        mv,
+        evaluation: Some(evaluation),
+    })
+}
+
+/// Values every empty cell for the side to move, keeping the best move for
+/// cells that take several (Wild symbols, Numerical numbers). `None` when
+/// the position is too big to solve, or when `cancel` is set meanwhile.
+pub fn analyse(state: &GameState, cancel: &AtomicBool) -> Option<Vec<CellValue>> {
+    let mut solver = Solver::new(cancel);
+    let mut values: Vec<CellValue> = Vec::new();
+    for mv in state.legal_moves() {
+        let mut after = state.clone();
+        make_move(&mut after, mv);
+        let outcome = solver.solve(&after)?.before_move(after.turn == state.turn);
+        let (board, cell) = match mv {
+            GameMove::PlaceOn(board, cell) => (board, cell),
+            // With gravity the mark lands lower down the column
+            GameMove::Place(cell) => (0, after.last_move.unwrap_or(cell)),
+            _ => (0, mv.target()),
+        };
+        match values
+            .iter_mut()
+            .find(|v| v.board == board && v.cell == cell)
+        {
+            Some(value) if outcome.rank() > value.outcome.rank() => value.outcome = outcome,
+            Some(_) => {}
+            None => values.push(CellValue {
+                board,
+                cell,
+                outcome,
+            }),
+        }
+    }
+    Some(values)
+}
+
+type MemoKey = (Board<Option<Symbol>>, Vec<Board<Option<Symbol>>>, Player);
+
+/// Solves positions, remembering every position seen so related positions
+/// (such as those of one game) share the work.
+pub struct Solver<'a> {
+    memo: HashMap<MemoKey, Outcome>,
+    cancel: &'a AtomicBool,
+    limit: usize,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(cancel: &'a AtomicBool) -> Self {
+        Self::with_limit(cancel, MAX_POSITIONS)
+    }
+
+    /// A solver that gives up after `limit` distinct positions, for searches
+    /// that must finish within a frame.
+    pub fn with_limit(cancel: &'a AtomicBool, limit: usize) -> Self {
+        Self {
+            memo: HashMap::new(),
+            cancel,
+            limit,
+        }
+    }
+
+    /// The value of `state` for its side to move, or `None` once the
+    /// position budget runs out or the search is cancelled.
+    pub fn solve(&mut self, state: &GameState) -> Option<Outcome> {
+        if state.is_over() {
+            return Some(match state.winner {
+                Some(winner) if winner == state.turn => Outcome::Win(0),
+                Some(_) => Outcome::Loss(0),
+                None => Outcome::Draw(0),
+            });
+        }
+
+        let key = (state.board.clone(), state.boards.clone(), state.turn);
+        if let Some(&outcome) = self.memo.get(&key) {
+            return Some(outcome);
+        }
+        if self.memo.len() >= self.limit || self.cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut best: Option<Outcome> = None;
+        for mv in state.legal_moves() {
+            let mut after = state.clone();
+            make_move(&mut after, mv);
+            let outcome = self.solve(&after)?.before_move(after.turn == state.turn);
+            if best.is_none_or(|b| outcome.rank() > b.rank()) {
+                best = Some(outcome);
+            }
+        }
+        // A position with no moves that isn't over counts as a draw
+        let outcome = best.unwrap_or(Outcome::Draw(0));
+        self.memo.insert(key, outcome);
+        Some(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::state::GameRules;
+
+    fn game(rules: GameRules) -> GameState {
+        let mut state = GameState {
+            rules: rules.normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        state
+    }
+
+    fn solve(state: &GameState) -> Outcome {
+        let cancel = AtomicBool::new(false);
+        Solver::new(&cancel).solve(state).unwrap()
+    }
+
+    #[test]
+    fn outcomes_flip_for_the_other_side() {
+        assert_eq!(Outcome::Win(2).before_move(false), Outcome::Loss(3));
+        assert_eq!(Outcome::Loss(2).before_move(false), Outcome::Win(3));
+        assert_eq!(Outcome::Draw(4).before_move(false), Outcome::Draw(5));
+        assert_eq!(Outcome::Win(2).before_move(true), Outcome::Win(3));
+        assert!(Outcome::Win(1).rank() > Outcome::Win(3).rank());
+        assert!(Outcome::Loss(5).rank() > Outcome::Loss(1).rank());
+    }
+
+    #[test]
+    fn tic_tac_toe_is_a_draw() {
+        let state = game(GameRules::default());
+        assert!(matches!(solve(&state), Outcome::Draw(_)));
+    }
+
+    #[test]
+    fn an_open_two_wins_next_move() {
+        let mut state = game(GameRules::default());
+        for pos in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            make_move(&mut state, GameMove::Place(pos));
+        }
+        assert_eq!(solve(&state), Outcome::Win(1));
+
+        let cancel = AtomicBool::new(false);
+        let best = best_move(&state, &cancel, MAX_POSITIONS).unwrap();
+        assert_eq!(best.mv, GameMove::Place((0, 2)));
+        assert_eq!(best.evaluation, Some(1.0));
+    }
+
+    #[test]
+    fn analysis_values_every_empty_cell() {
+        let mut state = game(GameRules::default());
+        make_move(&mut state, GameMove::Place((1, 1)));
+        let values = analyse(&state, &AtomicBool::new(false)).unwrap();
+        assert_eq!(values.len(), 8);
+        // O only survives in the corners
+        for value in values {
+            let corner = value.cell.0 != 1 && value.cell.1 != 1;
+            assert_eq!(matches!(value.outcome, Outcome::Draw(_)), corner);
+        }
+    }
+
+    #[test]
+    fn notakto_on_one_board_is_a_first_player_win() {
+        let state = game(GameRules {
+            mode: GameMode::Notakto,
+            ..Default::default()
+        });
+        assert!(matches!(solve(&state), Outcome::Win(_)));
+    }
+
+    #[test]
+    fn cancelled_solver_gives_up() {
+        let state = game(GameRules::default());
+        assert_eq!(Solver::new(&AtomicBool::new(true)).solve(&state), None);
+    }
+}
@@ -1,11 +1,46 @@
 use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use super::board::Board;
+use super::coach::HeldMove;
 use super::components::PlayerType;
+use super::fog;
+use super::hint::HintRecord;
+use super::lines::{hex_line_table, line_table, Line};
+use super::notakto;
+use super::quantum::{self, PendingCollapse, SpookyMark};
+use super::qubic;
+use super::review::MoveReview;
+use super::setup;
+use super::simultaneous::SecretRound;
+use super::solver::CellValue;
+use super::strategy::{MoveStrategy, StrategyMove};
 
-pub const BOARD_SIZE: usize = 3;
-pub const CELL_SIZE: f32 = 150.0; // world units per cell
+pub const DEFAULT_BOARD_SIZE: usize = 3;
+pub const MAX_BOARD_SIZE: usize = 7;
+pub const THREE_PLAYER_MIN_SIZE: usize = 4;
+/// Hint limits offered in the menu, in cycling order; `None` is unlimited.
+pub const HINT_LIMITS: [Option<u32>; 5] = [Some(3), Some(5), None, Some(0), Some(1)];
+pub const MAX_BOARD_COUNT: usize = 3; // Notakto boards
+pub const BOARD_PX: f32 = 450.0; // world units for the whole board
 pub const LINE_THICKNESS: f32 = 6.0;
 
+// Three Men's Morris draw rules
+pub const MORRIS_MOVE_LIMIT: usize = 50; // slides without a winner
+pub const REPETITION_LIMIT: usize = 3; // same position and side to move
+
+// Falling animation for gravity mode (world units per second squared)
+pub const FALL_ACCELERATION: f32 = 6000.0;
+
+// Reveal animation at the end of a fog-of-war game (seconds)
+pub const REVEAL_DURATION: f32 = 0.25;
+pub const REVEAL_STAGGER: f32 = 0.12;
+
 // Modern dark theme color palette
 pub const BG_COLOR: Color = Color::srgb(0.04, 0.05, 0.10); // Deep space blue
 pub const GRID_COLOR: Color = Color::srgb(0.31, 0.80, 0.77); // Cyan glow
@@ -18,26 +53,439 @@ pub const X_GLOW: Color = Color::srgba(1.0, 0.42, 0.42, 0.3); // Red glow
 pub const O_COLOR: Color = Color::srgb(0.31, 0.80, 0.77); // Electric cyan
 pub const O_GLOW: Color = Color::srgba(0.31, 0.80, 0.77, 0.3); // Cyan glow
 
+pub const TRIANGLE_COLOR: Color = Color::srgb(0.76, 0.56, 1.0); // Neon violet
+pub const TRIANGLE_GLOW: Color = Color::srgba(0.76, 0.56, 1.0, 0.3); // Violet glow
+
+// Opacity multiplier for a mark that is about to vanish
+pub const FADED_ALPHA: f32 = 0.35;
+
+pub const BLOCKED_COLOR: Color = Color::srgb(0.22, 0.24, 0.30); // Slate obstacle
+pub const DEAD_BOARD_COLOR: Color = Color::srgba(0.5, 0.5, 0.55, 0.35); // Notakto board with three in a row
+
 pub const WIN_COLOR: Color = Color::srgb(1.0, 0.85, 0.24); // Golden yellow
 pub const WIN_GLOW: Color = Color::srgba(1.0, 0.85, 0.24, 0.5); // Gold pulse
+pub const HINT_COLOR: Color = Color::srgba(0.45, 1.0, 0.55, 0.35); // Soft green suggestion
+pub const ANALYSIS_WIN: Color = Color::srgb(0.35, 0.95, 0.45); // Green: winning cell
+pub const ANALYSIS_DRAW: Color = Color::srgb(0.95, 0.85, 0.35); // Amber: drawing cell
+pub const ANALYSIS_LOSS: Color = Color::srgb(1.0, 0.35, 0.35); // Red: losing cell
 
 // UI colors for glass morphism
 pub const UI_BG: Color = Color::srgba(0.10, 0.12, 0.18, 0.85); // Glass background
 pub const UI_BORDER: Color = Color::srgba(0.31, 0.80, 0.77, 0.4); // Cyan border
 pub const UI_ACCENT: Color = Color::srgb(0.44, 1.0, 0.91); // Bright cyan
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
 pub enum Player {
     #[default]
     X,
     O,
+    /// Third player in three-player games, drawn as a triangle.
+    Triangle,
 }
 
 impl Player {
+    /// The opponent in a two-player game; three-player games follow
+    /// `GameState::next_player` instead.
     pub fn other(&self) -> Player {
         match self {
             Player::X => Player::O,
-            Player::O => Player::X,
+            Player::O | Player::Triangle => Player::X,
+        }
+    }
+
+    /// The symbol this player normally places.
+    pub fn symbol(&self) -> Symbol {
+        match self {
+            Player::X => Symbol::X,
+            Player::O => Symbol::O,
+            Player::Triangle => Symbol::Triangle,
+        }
+    }
+
+    /// Short name for labels; the triangle is spelled `T`.
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Player::X => "X",
+            Player::O => "O",
+            Player::Triangle => "T",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Player::X => X_COLOR,
+            Player::O => O_COLOR,
+            Player::Triangle => TRIANGLE_COLOR,
+        }
+    }
+
+    pub fn glow(&self) -> Color {
+        match self {
+            Player::X => X_GLOW,
+            Player::O => O_GLOW,
+            Player::Triangle => TRIANGLE_GLOW,
+        }
+    }
+}
+
+/// Every order the three players can take turns in, used by the menu.
+pub const TURN_ORDERS: [[Player; 3]; 6] = [
+    [Player::X, Player::O, Player::Triangle],
+    [Player::X, Player::Triangle, Player::O],
+    [Player::O, Player::X, Player::Triangle],
+    [Player::O, Player::Triangle, Player::X],
+    [Player::Triangle, Player::X, Player::O],
+    [Player::Triangle, Player::O, Player::X],
+];
+
+/// What is drawn in a cell. Usually tied to a player, but in Wild mode
+/// either player may place either symbol.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Symbol {
+    X,
+    O,
+    Triangle,
+    /// A number from 1 to 9 (Numerical mode): odd for X, even for O.
+    Number(u8),
+}
+
+impl Symbol {
+    /// The player who normally places this symbol.
+    pub fn owner(&self) -> Player {
+        match self {
+            Symbol::X => Player::X,
+            Symbol::O => Player::O,
+            Symbol::Triangle => Player::Triangle,
+            Symbol::Number(n) if n % 2 == 1 => Player::X,
+            Symbol::Number(_) => Player::O,
+        }
+    }
+}
+
+/// Sides in Order and Chaos: X plays Order, trying to make a line of either
+/// symbol, and O plays Chaos, trying to fill the board without one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Order,
+    Chaos,
+}
+
+impl Role {
+    pub fn player(&self) -> Player {
+        match self {
+            Role::Order => Player::X,
+            Role::Chaos => Player::O,
+        }
+    }
+
+    pub fn label(&self) -> &str {
+        match self {
+            Role::Order => "Order",
+            Role::Chaos => "Chaos",
+        }
+    }
+}
+
+/// How a full line of marks is judged when looking for a winner.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineRule {
+    /// Every mark in the line is the same symbol.
+    SameSymbol,
+    /// The numbers in the line add up to exactly this total.
+    SumTo(u32),
+}
+
+impl LineRule {
+    pub fn completes(&self, line: &[Symbol]) -> bool {
+        match *self {
+            LineRule::SameSymbol => line.windows(2).all(|pair| pair[0] == pair[1]),
+            LineRule::SumTo(total) => {
+                let numbers: Option<Vec<u32>> = line
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Number(n) => Some(u32::from(*n)),
+                        _ => None,
+                    })
+                    .collect();
+                numbers.is_some_and(|numbers| numbers.iter().sum::<u32>() == total)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    Vanishing,
+    Morris,
+    Quantum,
+    Wild,
+    Notakto,
+    Numerical,
+    OrderChaos,
+    Qubic,
+    Simultaneous,
+}
+
+impl GameMode {
+    pub fn label(&self) -> &str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Vanishing => "Vanishing Marks",
+            GameMode::Morris => "Three Men's Morris",
+            GameMode::Quantum => "Quantum",
+            GameMode::Wild => "Wild",
+            GameMode::Notakto => "Notakto",
+            GameMode::Numerical => "Numerical",
+            GameMode::OrderChaos => "Order and Chaos",
+            GameMode::Qubic => "Qubic",
+            GameMode::Simultaneous => "Simultaneous",
+        }
+    }
+
+    /// Cycles to the next mode, used by the menu's mode selector.
+    pub fn next(&self) -> GameMode {
+        match self {
+            GameMode::Classic => GameMode::Vanishing,
+            GameMode::Vanishing => GameMode::Morris,
+            GameMode::Morris => GameMode::Quantum,
+            GameMode::Quantum => GameMode::Wild,
+            GameMode::Wild => GameMode::Notakto,
+            GameMode::Notakto => GameMode::Numerical,
+            GameMode::Numerical => GameMode::OrderChaos,
+            GameMode::OrderChaos => GameMode::Qubic,
+            GameMode::Qubic => GameMode::Simultaneous,
+            GameMode::Simultaneous => GameMode::Classic,
+        }
+    }
+
+    /// Whether the gravity option applies; sliding or spooky marks can't fall.
+    pub fn allows_gravity(&self) -> bool {
+        matches!(
+            self,
+            GameMode::Classic | GameMode::Vanishing | GameMode::Wild
+        )
+    }
+
+    /// Board size for modes that only work on one board.
+    pub fn fixed_board_size(&self) -> Option<usize> {
+        match self {
+            GameMode::Quantum | GameMode::Notakto | GameMode::Numerical => Some(3),
+            GameMode::OrderChaos => Some(6),
+            GameMode::Qubic => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Win length for modes with a fixed board; a full row unless stated.
+    pub fn fixed_win_length(&self) -> Option<usize> {
+        match self {
+            GameMode::OrderChaos => Some(5),
+            _ => self.fixed_board_size(),
+        }
+    }
+
+    /// Whether the game is played on `GameState::boards`: several Notakto
+    /// boards or the layers of the Qubic cube.
+    pub fn uses_boards(&self) -> bool {
+        matches!(self, GameMode::Notakto | GameMode::Qubic)
+    }
+
+    /// Whether the hex grid option applies: single-board modes that don't
+    /// slide marks between neighbouring cells.
+    pub fn allows_hex(&self) -> bool {
+        !self.uses_boards() && *self != GameMode::Morris
+    }
+
+    /// Whether fog of war applies: every mark must belong to its placer.
+    pub fn allows_fog(&self) -> bool {
+        matches!(self, GameMode::Classic | GameMode::Vanishing)
+    }
+
+    /// Whether a third player can join; only plain placement modes.
+    pub fn allows_three_players(&self) -> bool {
+        matches!(self, GameMode::Classic | GameMode::Vanishing)
+    }
+
+    /// Whether obstacles and handicap marks apply: single-board modes where
+    /// cells hold plain X and O marks.
+    pub fn allows_setup(&self) -> bool {
+        matches!(
+            self,
+            GameMode::Classic
+                | GameMode::Vanishing
+                | GameMode::Morris
+                | GameMode::Wild
+                | GameMode::OrderChaos
+                | GameMode::Simultaneous
+        )
+    }
+
+    /// Whether both players may place either X or O.
+    pub fn free_symbol_choice(&self) -> bool {
+        matches!(self, GameMode::Wild | GameMode::OrderChaos)
+    }
+
+    pub fn line_rule(&self) -> LineRule {
+        match self {
+            GameMode::Numerical => LineRule::SumTo(15),
+            _ => LineRule::SameSymbol,
+        }
+    }
+
+    /// Whether completing any line wins for the mover, not the line's owner.
+    pub fn mover_wins_lines(&self) -> bool {
+        matches!(self, GameMode::Wild | GameMode::Numerical)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameMove {
+    /// Put a new mark on an empty cell.
+    Place((usize, usize)),
+    /// Slide an existing mark to an adjacent empty cell (Morris movement phase).
+    Slide {
+        from: (usize, usize),
+        to: (usize, usize),
+    },
+    /// Put a chosen symbol on an empty cell (Wild and Numerical modes).
+    PlaceSymbol((usize, usize), Symbol),
+    /// Place an entangled pair of spooky marks in two cells (Quantum mode).
+    Spooky((usize, usize), (usize, usize)),
+    /// Put a mark on one of several boards (Notakto boards, Qubic layers).
+    PlaceOn(usize, (usize, usize)),
+    /// Choose the cell the newest mark of a cycle collapses into (Quantum mode).
+    Collapse((usize, usize)),
+}
+
+impl GameMove {
+    /// The cell that ends up holding the moved or placed mark.
+    pub fn target(&self) -> (usize, usize) {
+        match *self {
+            GameMove::Place(pos)
+            | GameMove::PlaceSymbol(pos, _)
+            | GameMove::Collapse(pos)
+            | GameMove::Spooky(pos, _)
+            | GameMove::PlaceOn(_, pos) => pos,
+            GameMove::Slide { to, .. } => to,
+        }
+    }
+}
+
+/// Obstacles and handicap marks placed before the first move.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Setup {
+    #[default]
+    None,
+    BlockedCentre,
+    BlockedCorners,
+    RandomObstacles,
+    /// X starts with a mark in the centre.
+    CentreForX,
+    /// O starts with a mark in the centre, making up for moving second.
+    CentreForO,
+    /// Players arrange blocked cells and marks themselves before the game.
+    Custom,
+}
+
+impl Setup {
+    pub fn label(&self) -> &str {
+        match self {
+            Setup::None => "None",
+            Setup::BlockedCentre => "Blocked centre",
+            Setup::BlockedCorners => "Blocked corners",
+            Setup::RandomObstacles => "Random obstacles",
+            Setup::CentreForX => "X starts in centre",
+            Setup::CentreForO => "O starts in centre",
+            Setup::Custom => "Custom",
+        }
+    }
+
+    /// Cycles to the next preset, used by the menu's setup selector.
+    pub fn next(&self) -> Setup {
+        match self {
+            Setup::None => Setup::BlockedCentre,
+            Setup::BlockedCentre => Setup::BlockedCorners,
+            Setup::BlockedCorners => Setup::RandomObstacles,
+            Setup::RandomObstacles => Setup::CentreForX,
+            Setup::CentreForX => Setup::CentreForO,
+            Setup::CentreForO => Setup::Custom,
+            Setup::Custom => Setup::None,
+        }
+    }
+}
+
+/// Match rules picked in the menu; copied into `GameState` when a game starts.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GameRules {
+    pub mode: GameMode,
+    pub board_size: usize,
+    /// Marks in a row needed to win.
+    pub win_length: usize,
+    /// Marks drop to the lowest empty cell of the clicked column.
+    pub gravity: bool,
+    /// Cells are hexagons and lines run along the three hex axes.
+    pub hex: bool,
+    /// Lines continue across the board edges, as if the board were a torus.
+    pub wrap: bool,
+    pub setup: Setup,
+    /// Number of boards played at once in Notakto mode.
+    pub board_count: usize,
+    /// A third player joins (Classic and Vanishing modes, 4x4 and up).
+    pub three_players: bool,
+    /// Who moves first, second and third in a three-player game.
+    pub turn_order: [Player; 3],
+    /// Fog of war: players only see their own marks.
+    pub fog: bool,
+    /// Hints each human may ask for per game; `None` for no limit.
+    pub hint_limit: Option<u32>,
+    /// Warns human players before a move that throws away a win or a draw.
+    pub coach: bool,
+}
+
+impl GameRules {
+    /// Drops options that don't apply to the selected mode.
+    pub fn normalized(mut self) -> Self {
+        self.hex &= self.mode.allows_hex();
+        self.wrap &= !self.mode.uses_boards();
+        if !self.mode.allows_setup() {
+            self.setup = Setup::None;
+        }
+        self.three_players &= self.mode.allows_three_players();
+        if self.three_players {
+            self.board_size = self.board_size.max(THREE_PLAYER_MIN_SIZE);
+        }
+        self.fog &= self.mode.allows_fog();
+        // Columns don't line up on a hex board, so marks can't fall; with fog
+        // the landing row would give hidden marks away
+        self.gravity &= self.mode.allows_gravity() && !self.hex && !self.fog;
+        if let Some(size) = self.mode.fixed_board_size() {
+            self.board_size = size;
+        }
+        if let Some(length) = self.mode.fixed_win_length() {
+            self.win_length = length;
+        }
+        self
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            mode: GameMode::Classic,
+            board_size: DEFAULT_BOARD_SIZE,
+            win_length: DEFAULT_BOARD_SIZE,
+            gravity: false,
+            hex: false,
+            wrap: false,
+            setup: Setup::None,
+            board_count: 1,
+            three_players: false,
+            turn_order: TURN_ORDERS[0],
+            fog: false,
+            hint_limit: Some(3),
+            coach: false,
         }
     }
 }
@@ -49,29 +497,352 @@ pub enum AppState {
     Playing,
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone)]
 pub struct GameState {
-    pub board: [[Option<Player>; BOARD_SIZE]; BOARD_SIZE],
+    pub board: Board<Option<Symbol>>,
+    /// Move number at which the mark in each cell was placed (its age).
+    pub placed_at: Board<usize>,
+    pub last_move: Option<(usize, usize)>,
     pub turn: Player,
     pub winner: Option<Player>,
     pub moves: usize,
     pub winning_line: Option<WinningLine>,
+    pub draw: bool,
+    pub rules: GameRules,
+    /// How often each position occurred, for repetition draws in Morris mode.
+    pub repetitions: HashMap<(Board<Option<Symbol>>, Player), usize>,
+    /// Spooky marks per cell in Quantum mode; `board` holds collapsed marks.
+    pub spooky: Board<Vec<SpookyMark>>,
+    pub pending_collapse: Option<PendingCollapse>,
+    /// Player who completed a line at the same time as the winner but later,
+    /// earning half a point (Quantum mode).
+    pub runner_up: Option<Player>,
+    /// All boards in Notakto mode, or the layers of the cube in Qubic mode;
+    /// `board` is unused there.
+    pub boards: Vec<Board<Option<Symbol>>>,
+    /// Every winning line for the current board shape, shared between copies.
+    pub lines: Arc<Vec<Line>>,
+    /// Obstacles nobody can claim; they never hold a mark.
+    pub blocked: Board<bool>,
+    /// Players are arranging a custom setup and no moves are played yet.
+    pub setup_phase: bool,
+    /// The last custom setup, offered again when the game restarts.
+    pub custom_setup: Option<(Board<Option<Symbol>>, Board<bool>)>,
+    /// Cells whose mark everyone can see in fog-of-war games.
+    pub revealed: Board<bool>,
+    /// Player whose computer failed to produce a legal move, losing the game.
+    pub forfeited: Option<Player>,
+    /// Secret picks of the current round in Simultaneous mode.
+    pub round: SecretRound,
+    /// Fog of war and Simultaneous mode: the board stays hidden until the
+    /// player to move is ready, so players sharing a screen don't see each
+    /// other's marks or picks.
+    pub curtain: bool,
+    /// Counts resets, so work started for an earlier game can be recognised.
+    pub generation: u64,
+    /// Counts changes to the position this game: moves of every kind,
+    /// including probes and collapses, and edits to a custom setup.
+    pub changes: usize,
+    /// Hints shown this game, in move order.
+    pub hints: Vec<HintRecord>,
+    /// Every move played this game, in order, for the post-game review.
+    pub history: Vec<GameMove>,
+    /// The position before the first move, after any setup. With `history`
+    /// it records the game, seed included, for the review.
+    pub opening: Option<Arc<GameState>>,
+    /// A human's move the coach holds back until it is confirmed.
+    pub held: Option<HeldMove>,
+    /// Seeds every random decision of this game, from obstacles to the
+    /// computer's picks, so the game can be played again exactly.
+    pub seed: u64,
 }
 
 impl GameState {
+    /// Resets the board for a new game with its own `seed`.
+    pub fn restart(&mut self, seed: u64) {
+        self.seed = seed;
+        self.reset();
+    }
+
     pub fn reset(&mut self) {
-        self.board = [[None; BOARD_SIZE]; BOARD_SIZE];
-        self.turn = Player::X;
+        self.generation += 1;
+        self.changes = 0;
+        self.board = Board::new(self.rules.board_size);
+        self.placed_at = Board::new(self.rules.board_size);
+        self.last_move = None;
+        self.turn = self.players()[0];
         self.winner = None;
         self.moves = 0;
         self.winning_line = None;
+        self.draw = false;
+        self.repetitions.clear();
+        self.spooky = Board::new(self.rules.board_size);
+        self.pending_collapse = None;
+        self.runner_up = None;
+        let size = self.rules.board_size;
+        let layers = match self.rules.mode {
+            GameMode::Notakto => self.rules.board_count,
+            GameMode::Qubic => size,
+            _ => 0,
+        };
+        self.boards = vec![Board::new(size); layers];
+        self.lines = Arc::new(if self.rules.hex {
+            hex_line_table(size, self.rules.win_length, self.rules.wrap)
+        } else {
+            line_table(layers.max(1), size, self.rules.win_length, self.rules.wrap)
+        });
+        self.blocked = Board::new(size);
+        self.setup_phase = false;
+        self.revealed = Board::new(size);
+        self.curtain = self.rules.fog;
+        self.round = SecretRound::default();
+        self.forfeited = None;
+        self.hints.clear();
+        self.history.clear();
+        self.opening = None;
+        self.held = None;
+        setup::apply(self);
+        fog::reveal_marks(self);
+    }
+
+    /// Hints shown to `player` this game.
+    pub fn hints_used(&self, player: Player) -> u32 {
+        self.hints
+            .iter()
+            .filter(|hint| hint.player == player)
+            .count() as u32
+    }
+
+    /// Random numbers for a decision in this position. The same seed and
+    /// position always give the same numbers, however many were drawn
+    /// elsewhere.
+    pub fn rng(&self) -> StdRng {
+        let position = (self.moves as u64) << 2 | self.turn as u64;
+        StdRng::seed_from_u64(self.seed ^ position.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    /// Ends the game with `player` losing; the next player takes the win.
+    pub fn forfeit(&mut self, player: Player) {
+        self.forfeited = Some(player);
+        self.winner = Some(self.next_player(player));
+    }
+
+    /// Everyone playing, in turn order.
+    pub fn players(&self) -> Vec<Player> {
+        if self.rules.three_players {
+            self.rules.turn_order.to_vec()
+        } else {
+            vec![Player::X, Player::O]
+        }
+    }
+
+    /// Who moves after `player`.
+    pub fn next_player(&self, player: Player) -> Player {
+        let players = self.players();
+        let index = players.iter().position(|&p| p == player).unwrap_or(0);
+        players[(index + 1) % players.len()]
+    }
+
+    /// Everyone but `player`, in the order they move after them.
+    pub fn opponents(&self, player: Player) -> Vec<Player> {
+        let mut opponents = Vec::new();
+        let mut next = self.next_player(player);
+        while next != player {
+            opponents.push(next);
+            next = self.next_player(next);
+        }
+        opponents
+    }
+
+    /// The mark in a `(layer, row, col)` cell; flat boards use layer 0.
+    pub fn symbol_at(&self, (layer, row, col): (usize, usize, usize)) -> Option<Symbol> {
+        if self.rules.mode.uses_boards() {
+            self.boards[layer][(row, col)]
+        } else {
+            self.board[(row, col)]
+        }
+    }
+
+    /// Whether a cell can take a mark: empty and not blocked.
+    pub fn is_open(&self, pos: (usize, usize)) -> bool {
+        self.board[pos].is_none() && !self.blocked[pos]
+    }
+
+    /// Whether every playable cell holds a mark; blocked cells don't count.
+    pub fn is_full(&self) -> bool {
+        self.board.iter().all(|(pos, _)| !self.is_open(pos))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.draw
+    }
+
+    /// Most marks a player may have on the board in vanishing and Morris modes.
+    pub fn mark_limit(&self) -> usize {
+        self.rules.win_length
+    }
+
+    /// In Morris mode, once both players have placed all their marks turns
+    /// consist of sliding a mark instead.
+    pub fn in_movement_phase(&self) -> bool {
+        self.rules.mode == GameMode::Morris && self.mark_count(self.turn) >= self.mark_limit()
+    }
+
+    pub fn mark_count(&self, player: Player) -> usize {
+        self.board
+            .iter()
+            .filter(|&(_, &cell)| cell == Some(player.symbol()))
+            .count()
+    }
+
+    /// Lowest open row in `col`, where a dropped mark lands in gravity mode.
+    pub fn landing_row(&self, col: usize) -> Option<usize> {
+        (0..self.board.size()).find(|&row| self.is_open((row, col)))
+    }
+
+    /// Moves the side to move may play; one per non-full column with gravity.
+    pub fn legal_moves(&self) -> Vec<GameMove> {
+        if self.rules.mode == GameMode::Quantum {
+            quantum::legal_moves(self)
+        } else if self.rules.mode == GameMode::Notakto {
+            notakto::legal_moves(self)
+        } else if self.rules.mode == GameMode::Qubic {
+            qubic::legal_moves(self)
+        } else if self.in_movement_phase() {
+            self.board
+                .iter()
+                .filter(|&(_, &cell)| cell == Some(self.turn.symbol()))
+                .flat_map(|(from, _)| {
+                    self.slide_targets(from)
+                        .into_iter()
+                        .map(move |to| GameMove::Slide { from, to })
+                })
+                .collect()
+        } else {
+            let cells: Vec<(usize, usize)> = if self.rules.gravity {
+                (0..self.board.size())
+                    .filter_map(|col| self.landing_row(col).map(|row| (row, col)))
+                    .collect()
+            } else {
+                self.board
+                    .iter()
+                    .map(|(pos, _)| pos)
+                    .filter(|&pos| self.is_open(pos) || fog::is_hidden_from(self, self.turn, pos))
+                    .collect()
+            };
+            if self.rules.mode.free_symbol_choice() {
+                cells
+                    .into_iter()
+                    .flat_map(|pos| [Symbol::X, Symbol::O].map(|s| GameMove::PlaceSymbol(pos, s)))
+                    .collect()
+            } else if self.rules.mode == GameMode::Numerical {
+                let numbers = self.remaining_numbers(self.turn);
+                cells
+                    .into_iter()
+                    .flat_map(|pos| {
+                        numbers
+                            .iter()
+                            .map(move |&n| GameMove::PlaceSymbol(pos, Symbol::Number(n)))
+                    })
+                    .collect()
+            } else {
+                cells.into_iter().map(GameMove::Place).collect()
+            }
+        }
+    }
+
+    /// The side `player` plays in Order and Chaos; `None` in other modes.
+    pub fn role(&self, player: Player) -> Option<Role> {
+        if self.rules.mode != GameMode::OrderChaos {
+            return None;
+        }
+        Some(match player {
+            Player::X => Role::Order,
+            Player::O | Player::Triangle => Role::Chaos,
+        })
+    }
+
+    /// Numbers `player` has not placed yet in Numerical mode: odd ones for X,
+    /// even ones for O, each usable once.
+    pub fn remaining_numbers(&self, player: Player) -> Vec<u8> {
+        (1..=9)
+            .filter(|&n| Symbol::Number(n).owner() == player)
+            .filter(|&n| {
+                !self
+                    .board
+                    .iter()
+                    .any(|(_, &cell)| cell == Some(Symbol::Number(n)))
+            })
+            .collect()
+    }
+
+    /// Empty cells adjacent (including diagonally) to `from`.
+    pub fn slide_targets(&self, from: (usize, usize)) -> Vec<(usize, usize)> {
+        let (row, col) = (from.0 as isize, from.1 as isize);
+        let mut targets = Vec::new();
+        for dr in -1..=1 {
+            for dc in -1..=1 {
+                if let Some(pos) = self.board.cell(row + dr, col + dc) {
+                    if pos != from && self.is_open(pos) {
+                        targets.push(pos);
+                    }
+                }
+            }
+        }
+        targets
+    }
+
+    /// The player's oldest mark still on the board.
+    pub fn oldest_mark(&self, player: Player) -> Option<(usize, usize)> {
+        self.board
+            .iter()
+            .filter(|&(_, &cell)| cell == Some(player.symbol()))
+            .map(|(pos, _)| pos)
+            .min_by_key(|&pos| self.placed_at[pos])
     }
+
+    /// The mark that disappears when `player` places their next mark, if any.
+    /// Each player keeps at most `mark_limit` marks (three on the classic board).
+    pub fn next_to_vanish(&self, player: Player) -> Option<(usize, usize)> {
+        if self.rules.mode != GameMode::Vanishing || self.mark_count(player) < self.mark_limit() {
+            return None;
+        }
+        self.oldest_mark(player)
+    }
+}
+
+/// Mark picked up by a human in the Morris movement phase.
+#[derive(Resource, Default)]
+pub struct MoveSelection {
+    pub selected: Option<(usize, usize)>,
+    /// Number picked in the Numerical mode picker.
+    pub number: Option<u8>,
 }
 
 #[derive(Resource)]
 pub struct PlayerConfig {
     pub x_type: PlayerType,
     pub o_type: PlayerType,
+    /// The third player in three-player games.
+    pub triangle_type: PlayerType,
+}
+
+impl PlayerConfig {
+    pub fn type_of(&self, player: Player) -> PlayerType {
+        match player {
+            Player::X => self.x_type,
+            Player::O => self.o_type,
+            Player::Triangle => self.triangle_type,
+        }
+    }
+
+    pub fn set_type(&mut self, player: Player, player_type: PlayerType) {
+        match player {
+            Player::X => self.x_type = player_type,
+            Player::O => self.o_type = player_type,
+            Player::Triangle => self.triangle_type = player_type,
+        }
+    }
 }
 
 impl Default for PlayerConfig {
@@ -79,15 +850,61 @@ impl Default for PlayerConfig {
         Self {
             x_type: PlayerType::Human,
             o_type: PlayerType::Human,
+            triangle_type: PlayerType::Human,
+        }
+    }
+}
+
+/// Hands each game its seed. The first game takes the seed given with
+/// `--seed`, or a random one; the seeds of later games follow from it.
+#[derive(Resource)]
+pub struct GameRng {
+    rng: StdRng,
+    next: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: Option<u64>) -> Self {
+        let first = seed.unwrap_or_else(rand::random);
+        Self {
+            rng: StdRng::seed_from_u64(first),
+            next: first,
         }
     }
+
+    /// The seed for a new game.
+    pub fn next_seed(&mut self) -> u64 {
+        let next = self.rng.gen();
+        std::mem::replace(&mut self.next, next)
+    }
 }
 
 #[derive(Resource, Default)]
 pub struct Score {
     pub x_wins: u32,
     pub o_wins: u32,
+    pub triangle_wins: u32,
     pub draws: u32,
+    /// Half points for the later of two simultaneous lines (Quantum mode).
+    pub x_half_points: u32,
+    pub o_half_points: u32,
+    /// Hints asked for over the session.
+    pub x_hints: u32,
+    pub o_hints: u32,
+    pub triangle_hints: u32,
+    /// Moves the coach warned about, and how many of them were taken back.
+    pub coach_warnings: u32,
+    pub coach_take_backs: u32,
+}
+
+impl Score {
+    pub fn record_hint(&mut self, player: Player) {
+        match player {
+            Player::X => self.x_hints += 1,
+            Player::O => self.o_hints += 1,
+            Player::Triangle => self.triangle_hints += 1,
+        }
+    }
 }
 
 #[derive(Resource)]
@@ -103,8 +920,181 @@ impl Default for ComputerMoveTimer {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Identifies the position a move was worked out for.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PositionKey {
+    generation: u64,
+    changes: usize,
+}
+
+impl PositionKey {
+    pub fn of(state: &GameState) -> Self {
+        Self {
+            generation: state.generation,
+            changes: state.changes,
+        }
+    }
+}
+
+/// A computer move being worked out on the `AsyncComputeTaskPool`.
+/// Dropping it tells the strategy to stop thinking.
+pub struct PendingMove {
+    pub task: Task<Option<StrategyMove>>,
+    /// Strategy doing the thinking.
+    pub name: &'static str,
+    position: PositionKey,
+    cancel: Arc<AtomicBool>,
+}
+
+impl PendingMove {
+    /// Starts `strategy` on a move for `side` in `view`, the board of `state`
+    /// as that side sees it. Without a strategy the task gives no move.
+    pub fn spawn(
+        strategy: Option<Arc<dyn MoveStrategy>>,
+        name: &'static str,
+        view: GameState,
+        side: Player,
+        state: &GameState,
+    ) -> Self {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = cancel.clone();
+        let task = AsyncComputeTaskPool::get()
+            .spawn(async move { strategy.and_then(|s| s.choose_move(&view, side, &flag)) });
+        Self {
+            task,
+            name,
+            position: PositionKey::of(state),
+            cancel,
+        }
+    }
+
+    /// Whether the move is still wanted: same game, same position.
+    pub fn is_for(&self, state: &GameState) -> bool {
+        self.position == PositionKey::of(state)
+    }
+}
+
+impl Drop for PendingMove {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A suggested move for the human to move, with a short explanation.
+pub struct ShownHint {
+    pub mv: GameMove,
+    pub reason: String,
+    pub position: PositionKey,
+}
+
+/// The hint being worked out or on show.
+#[derive(Resource, Default)]
+pub struct HintState {
+    pub pending: Option<PendingMove>,
+    pub shown: Option<ShownHint>,
+}
+
+/// The computer move in progress, if any. Dropping it cancels the search.
+#[derive(Resource, Default)]
+pub struct ComputerThinking {
+    pub pending: Option<PendingMove>,
+}
+
+/// The analysis overlay: every empty cell coloured by its solved value for
+/// the side to move.
+#[derive(Resource, Default)]
+pub struct AnalysisOverlay {
+    pub enabled: bool,
+    /// Solver run for `position`; `None` inside when it was too big to solve.
+    pub task: Option<Task<Option<Vec<CellValue>>>>,
+    /// Set to stop the running solver, which dropping the task can't do.
+    pub cancel: Arc<AtomicBool>,
+    /// Position on show or being solved.
+    pub position: Option<PositionKey>,
+    pub status: &'static str,
+}
+
+impl AnalysisOverlay {
+    /// Stops any solver run in progress.
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.task = None;
+    }
+}
+
+/// The post-game review: the finished game replayed move by move, with
+/// every move graded by the solver.
+#[derive(Resource, Default)]
+pub struct GameReview {
+    pub active: bool,
+    /// Solver run grading the moves.
+    pub task: Option<Task<Vec<MoveReview>>>,
+    /// Set to stop the running solver, which dropping the task can't do.
+    pub cancel: Arc<AtomicBool>,
+    /// Grades in move order, once the solver is done.
+    pub moves: Vec<MoveReview>,
+    /// Moves played on the board being shown.
+    pub step: usize,
+    /// The board after `step` moves.
+    pub position: Option<GameState>,
+}
+
+impl GameReview {
+    /// The board to draw: the reviewed position while reviewing a finished
+    /// game, otherwise the game itself.
+    pub fn shown<'a>(&'a self, state: &'a GameState) -> &'a GameState {
+        match &self.position {
+            Some(position) if self.active && state.is_over() => position,
+            _ => state,
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        *self = Self::default();
+    }
+}
+
+/// A completed line as runs of adjacent cells, each given by its first and
+/// last cell as `(col, row, layer)`. A line wrapping around the board edges
+/// has one segment on each side of every edge it crosses.
+#[derive(Clone, Debug)]
 pub struct WinningLine {
-    pub start: UVec2,
-    pub end: UVec2,
+    pub segments: Vec<(UVec3, UVec3)>,
+    /// Step from one cell of the line to the next, ignoring wrapping.
+    pub step: IVec3,
+}
+
+impl WinningLine {
+    pub fn spanning(line: &[(usize, usize, usize)]) -> Self {
+        let corner = |&(layer, row, col): &(usize, usize, usize)| {
+            UVec3::new(col as u32, row as u32, layer as u32)
+        };
+        // A jump of more than one cell along any axis means the line wrapped
+        let wraps = |a: UVec3, b: UVec3| (b.as_ivec3() - a.as_ivec3()).abs().max_element() > 1;
+
+        let mut segments: Vec<(UVec3, UVec3)> = Vec::new();
+        for cell in line.iter().map(corner) {
+            match segments.last_mut() {
+                Some((_, end)) if !wraps(*end, cell) => *end = cell,
+                _ => segments.push((cell, cell)),
+            }
+        }
+
+        let step = match line {
+            [a, b, ..] => {
+                let d = corner(b).as_ivec3() - corner(a).as_ivec3();
+                // A wrapped component jumps back across the board
+                IVec3::select(d.abs().cmpgt(IVec3::ONE), -d.signum(), d)
+            }
+            _ => IVec3::ZERO,
+        };
+        Self { segments, step }
+    }
+
+    /// The first cell of the line as `(layer, row, col)`.
+    pub fn first_cell(&self) -> (usize, usize, usize) {
+        let start = self.segments[0].0;
+        (start.z as usize, start.y as usize, start.x as usize)
+    }
 }
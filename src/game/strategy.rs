@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use super::state::{GameMove, GameState, Player};
+
+/// A move picked by a strategy, with its own assessment of the position
+/// when it has one.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StrategyMove {
+    pub mv: GameMove,
+    /// How good the position is for the side to move, from -1 (lost) to 1
+    /// (won); `None` for strategies that don't evaluate.
+    pub evaluation: Option<f32>,
+}
+
+impl StrategyMove {
+    pub fn new(mv: GameMove) -> Self {
+        Self {
+            mv,
+            evaluation: None,
+        }
+    }
+}
+
+/// A computer opponent. It sees the board as its player does (hidden marks
+/// are removed in fog of war, and the other picks of a Simultaneous round)
+/// and picks a move for `side`, the side to move.
+pub trait MoveStrategy: Send + Sync + 'static {
+    /// Shown in the player selection menu as "Computer <name>".
+    fn name(&self) -> &'static str;
+
+    /// Long searches stop early once `cancel` is set; the move is no longer
+    /// wanted then, so what they return doesn't matter.
+    fn choose_move(
+        &self,
+        view: &GameState,
+        side: Player,
+        cancel: &AtomicBool,
+    ) -> Option<StrategyMove>;
+}
+
+/// Every computer opponent, in menu order; `PlayerType::Computer` refers to
+/// them by name.
+#[derive(Resource, Default)]
+pub struct StrategyRegistry {
+    strategies: Vec<Arc<dyn MoveStrategy>>,
+}
+
+impl StrategyRegistry {
+    /// Adds a strategy, replacing any registered under the same name.
+    pub fn register(&mut self, strategy: impl MoveStrategy) {
+        self.strategies.retain(|s| s.name() != strategy.name());
+        self.strategies.push(Arc::new(strategy));
+    }
+
+    /// Shared, so a move can be worked out on another thread.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn MoveStrategy>> {
+        self.strategies.iter().find(|s| s.name() == name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.strategies.iter().map(|s| s.name())
+    }
+}
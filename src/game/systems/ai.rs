@@ -1,139 +1,468 @@
 use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once};
 use rand::Rng;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
 use crate::game::components::*;
+use crate::game::fog;
+use crate::game::lines::check_winner;
+use crate::game::notakto;
+use crate::game::quantum;
+use crate::game::qubic;
+use crate::game::simultaneous;
 use crate::game::state::*;
+use crate::game::strategy::{MoveStrategy, StrategyMove, StrategyRegistry};
 
-use super::rendering::check_winner;
+/// Picks a random legal move.
+pub struct EasyStrategy;
 
+impl MoveStrategy for EasyStrategy {
+    fn name(&self) -> &'static str {
+        "Easy"
+    }
+
+    fn choose_move(
+        &self,
+        view: &GameState,
+        _side: Player,
+        _cancel: &AtomicBool,
+    ) -> Option<StrategyMove> {
+        find_easy_move(view).map(StrategyMove::new)
+    }
+}
+
+/// Wins and blocks where it can, with dedicated solvers for some modes.
+pub struct HardStrategy;
+
+impl MoveStrategy for HardStrategy {
+    fn name(&self) -> &'static str {
+        "Hard"
+    }
+
+    fn choose_move(
+        &self,
+        view: &GameState,
+        _side: Player,
+        _cancel: &AtomicBool,
+    ) -> Option<StrategyMove> {
+        find_hard_move(view).map(StrategyMove::new)
+    }
+}
+
+/// The computer opponents available out of the box.
+pub fn builtin_strategies() -> StrategyRegistry {
+    let mut registry = StrategyRegistry::default();
+    registry.register(EasyStrategy);
+    registry.register(HardStrategy);
+    registry
+}
+
+/// Thinks on the `AsyncComputeTaskPool` so long searches don't stall
+/// rendering, and plays the move once both the search and the move timer
+/// are done; the timer keeps quick strategies from moving instantly.
 pub fn computer_player(
     time: Res<Time>,
     mut timer: ResMut<ComputerMoveTimer>,
+    mut thinking: ResMut<ComputerThinking>,
     mut state: ResMut<GameState>,
     player_config: Res<PlayerConfig>,
+    registry: Res<StrategyRegistry>,
 ) {
-    if state.winner.is_some() {
-        return;
+    // A search for a position that is gone (restart, game over) is
+    // dropped, which tells the strategy to stop
+    if thinking
+        .pending
+        .as_ref()
+        .is_some_and(|pending| !pending.is_for(&state) || state.is_over())
+    {
+        thinking.pending = None;
     }
 
-    let current_player_type = match state.turn {
-        Player::X => player_config.x_type,
-        Player::O => player_config.o_type,
-    };
+    if state.is_over() || state.setup_phase {
+        return;
+    }
 
-    if current_player_type == PlayerType::Human {
+    let PlayerType::Computer(name) = player_config.type_of(state.turn) else {
         timer.timer.reset();
         return;
-    }
+    };
 
     timer.timer.tick(time.delta());
 
+    let pending = thinking.pending.get_or_insert_with(|| {
+        // In fog of war the computer only knows what its player sees
+        let view = fog::view_for(&state, state.turn);
+        PendingMove::spawn(registry.get(name), name, view, state.turn, &state)
+    });
+
     if !timer.timer.is_finished() {
         return;
     }
+    let Some(choice) = block_on(poll_once(&mut pending.task)) else {
+        return;
+    };
+    thinking.pending = None;
 
-    // Make computer move
-    if let Some((row, col)) = find_computer_move(&state.board, current_player_type, state.turn) {
-        make_move(&mut state, row, col);
-
-        // Reset timer with random delay (200-800ms)
-        let mut rng = rand::thread_rng();
-        let delay = rng.gen_range(0.2..0.8);
-        timer.timer = Timer::from_seconds(delay, TimerMode::Once);
+    // A computer that can't come up with a legal move forfeits
+    let legal = state.legal_moves();
+    match choice {
+        Some(choice) if legal.contains(&choice.mv) => {
+            if let Some(evaluation) = choice.evaluation {
+                debug!("{name} rates its position at {evaluation:.2}");
+            }
+            make_move(&mut state, choice.mv);
+        }
+        _ => {
+            let player = state.turn;
+            warn!("Computer {name} gave no legal move; Player {player:?} forfeits");
+            state.forfeit(player);
+            return;
+        }
     }
+
+    // Reset timer with random delay (200-800ms)
+    let delay = state.rng().gen_range(0.2..0.8);
+    timer.timer = Timer::from_seconds(delay, TimerMode::Once);
 }
 
-fn find_computer_move(
-    board: &[[Option<Player>; BOARD_SIZE]; BOARD_SIZE],
-    player_type: PlayerType,
-    player: Player,
-) -> Option<(usize, usize)> {
-    match player_type {
-        PlayerType::Human => None,
-        PlayerType::ComputerEasy => find_easy_move(board),
-        PlayerType::ComputerHard => find_hard_move(board, player),
+fn find_easy_move(state: &GameState) -> Option<GameMove> {
+    let moves = state.legal_moves();
+    if moves.is_empty() {
+        return None;
     }
+
+    let idx = state.rng().gen_range(0..moves.len());
+    Some(moves[idx])
 }
 
-fn find_easy_move(board: &[[Option<Player>; BOARD_SIZE]; BOARD_SIZE]) -> Option<(usize, usize)> {
-    let mut empty_cells = Vec::new();
-    for (row, row_data) in board.iter().enumerate() {
-        for (col, cell) in row_data.iter().enumerate() {
-            if cell.is_none() {
-                empty_cells.push((row, col));
-            }
+pub fn find_hard_move(state: &GameState) -> Option<GameMove> {
+    match state.rules.mode {
+        GameMode::Quantum => return find_quantum_move(state),
+        GameMode::Notakto => return notakto::best_move(state),
+        GameMode::Qubic => return qubic::best_move(state),
+        GameMode::Simultaneous => return simultaneous::best_move(state),
+        _ => {}
+    }
+
+    let player = state.turn;
+
+    // Try to win
+    if let Some(mv) = find_winning_move(state, player) {
+        return Some(mv);
+    }
+
+    let moves = state.legal_moves();
+
+    // Block opponents from winning by taking the cell they need, starting
+    // with whoever moves next
+    for opponent in state.opponents(player) {
+        let Some(threat) = find_winning_move(state, opponent) else {
+            continue;
+        };
+        // In vanishing mode our own oldest mark disappears when we block, so
+        // only block if that doesn't hand the opponent a win next turn.
+        if let Some(&mv) = moves
+            .iter()
+            .find(|mv| mv.target() == threat.target() && !gives_opponent_win(state, **mv))
+        {
+            return Some(mv);
+        }
+    }
+
+    // Avoid moves that set up an immediate win for the opponent, e.g. filling
+    // the cell below their threat in gravity mode or vacating a blocking cell
+    let safe: Vec<GameMove> = moves
+        .iter()
+        .copied()
+        .filter(|&mv| !gives_opponent_win(state, mv))
+        .collect();
+    let candidates = if safe.is_empty() { moves } else { safe };
+
+    // Take center if available, then a corner
+    let last = state.board.size() - 1;
+    let mid = last / 2;
+    for pos in [(mid, mid), (0, 0), (0, last), (last, 0), (last, last)] {
+        if let Some(&mv) = candidates.iter().find(|mv| mv.target() == pos) {
+            return Some(mv);
         }
     }
 
-    if empty_cells.is_empty() {
+    // Take any available position
+    if candidates.is_empty() {
         return None;
     }
+    Some(candidates[state.rng().gen_range(0..candidates.len())])
+}
+
+/// Picks the move with the best outcome once any collapse it triggers is
+/// resolved, assuming the opponent chooses the collapse worst for us.
+fn find_quantum_move(state: &GameState) -> Option<GameMove> {
+    let me = state.turn;
+    let outcome = |after: &GameState| match after.winner {
+        Some(p) if p == me && after.runner_up.is_none() => 4,
+        Some(p) if p == me => 3,
+        Some(_) if after.runner_up == Some(me) => -3,
+        Some(_) => -4,
+        None => 0,
+    };
+    // Prefer cells on many lines: centre, then corners, then edges
+    let last = state.board.size() - 1;
+    let lines_through =
+        |(row, col): (usize, usize)| 2 + usize::from(row == col) + usize::from(row + col == last);
+
+    let mut rng = state.rng();
+    let mut best: Option<(i32, usize, u32, GameMove)> = None;
+    for mv in state.legal_moves() {
+        let mut after = state.clone();
+        make_move(&mut after, mv);
+
+        let value = match (after.pending_collapse, mv) {
+            // We closed a cycle; the opponent picks the collapse
+            (Some(pending), GameMove::Spooky(..)) => pending
+                .cells
+                .iter()
+                .map(|&cell| {
+                    let mut collapsed = after.clone();
+                    make_move(&mut collapsed, GameMove::Collapse(cell));
+                    outcome(&collapsed)
+                })
+                .min()
+                .unwrap_or(0),
+            _ => outcome(&after),
+        };
+        let reach = match mv {
+            GameMove::Spooky(a, b) => lines_through(a) + lines_through(b),
+            other => lines_through(other.target()),
+        };
+
+        let key = (value, reach, rng.gen());
+        if best.is_none_or(|(v, r, t, _)| key > (v, r, t)) {
+            best = Some((key.0, key.1, key.2, mv));
+        }
+    }
+    best.map(|(_, _, _, mv)| mv)
+}
 
-    let mut rng = rand::thread_rng();
-    let idx = rng.gen_range(0..empty_cells.len());
-    Some(empty_cells[idx])
+/// Finds a move that wins for `player` if it were their turn. Moves are
+/// simulated on a copy of the state so mode rules (vanishing marks, gravity) apply.
+pub fn find_winning_move(state: &GameState, player: Player) -> Option<GameMove> {
+    let mut test_state = state.clone();
+    test_state.turn = player;
+    test_state.legal_moves().into_iter().find(|&mv| {
+        let mut after = test_state.clone();
+        make_move(&mut after, mv);
+        after.winner == Some(player)
+    })
 }
 
-fn find_hard_move(
-    board: &[[Option<Player>; BOARD_SIZE]; BOARD_SIZE],
-    player: Player,
-) -> Option<(usize, usize)> {
-    // Try to win
-    if let Some(pos) = find_winning_move(board, player) {
-        return Some(pos);
+/// Whether playing `mv` hands an opponent the game: at once, as when Chaos
+/// completes Order's line with an X, or through an immediate winning reply.
+pub fn gives_opponent_win(state: &GameState, mv: GameMove) -> bool {
+    let mut after = state.clone();
+    make_move(&mut after, mv);
+    if after.is_over() {
+        return after.winner.is_some_and(|winner| winner != state.turn);
     }
+    find_winning_move(&after, after.turn).is_some()
+}
 
-    // Block opponent from winning
-    if let Some(pos) = find_winning_move(board, player.other()) {
-        return Some(pos);
+/// Plays `mv` for the side to move. With gravity only the column of a
+/// placement matters; the mark lands on the lowest empty cell.
+pub fn make_move(state: &mut GameState, mv: GameMove) {
+    let player = state.turn;
+    state.changes += 1;
+    if state.history.is_empty() {
+        state.opening = Some(Arc::new(state.clone()));
     }
+    state.history.push(mv);
 
-    // Take center if available
-    if board[1][1].is_none() {
-        return Some((1, 1));
+    match mv {
+        GameMove::Spooky(a, b) => return quantum::play_spooky(state, a, b),
+        GameMove::Collapse(cell) => return quantum::resolve_collapse(state, cell),
+        GameMove::PlaceOn(index, pos) if state.rules.mode == GameMode::Qubic => {
+            return qubic::play(state, index, pos)
+        }
+        GameMove::PlaceOn(index, pos) => return notakto::play(state, index, pos),
+        GameMove::Place(pos) if fog::is_hidden_from(state, player, pos) => {
+            return fog::probe(state, pos)
+        }
+        GameMove::Place(pos) if state.rules.mode == GameMode::Simultaneous => {
+            return simultaneous::commit(state, pos)
+        }
+        GameMove::Place(_) | GameMove::PlaceSymbol(..) | GameMove::Slide { .. } => {}
     }
 
-    // Take a corner
-    for &(r, c) in &[(0, 0), (0, 2), (2, 0), (2, 2)] {
-        if board[r][c].is_none() {
-            return Some((r, c));
+    // Vanishing mode: a new mark beyond the limit removes the oldest one.
+    if let Some(pos) = state.next_to_vanish(player) {
+        state.board[pos] = None;
+        state.revealed[pos] = false;
+        if state.rules.gravity {
+            settle_column(state, pos.1);
         }
     }
 
-    // Take any available position
-    find_easy_move(board)
+    let (pos, symbol) = match mv {
+        GameMove::Place(pos) => (pos, player.symbol()),
+        GameMove::PlaceSymbol(pos, symbol) => (pos, symbol),
+        GameMove::Slide { from, to } => {
+            state.board[from] = None;
+            (to, player.symbol())
+        }
+        GameMove::Spooky(..) | GameMove::Collapse(_) | GameMove::PlaceOn(..) => {
+            unreachable!("handled above")
+        }
+    };
+    let (row, col) = match mv {
+        GameMove::Place((_, col)) | GameMove::PlaceSymbol((_, col), _) if state.rules.gravity => {
+            match state.landing_row(col) {
+                Some(row) => (row, col),
+                None => return,
+            }
+        }
+        _ => pos,
+    };
+
+    state.board[(row, col)] = Some(symbol);
+    state.placed_at[(row, col)] = state.moves;
+    state.last_move = Some((row, col));
+    state.moves += 1;
+
+    if let Some(line) = check_winner(state) {
+        // Any line wins for Order; in Wild and Numerical modes whoever
+        // completes a line wins, whatever its marks; otherwise settling a
+        // column can complete a line for either player
+        state.winner = if state.rules.mode == GameMode::OrderChaos {
+            Some(Role::Order.player())
+        } else if state.rules.mode.mover_wins_lines() {
+            Some(player)
+        } else {
+            state.symbol_at(line.first_cell()).map(|s| s.owner())
+        };
+        state.winning_line = Some(line);
+        return;
+    }
+
+    state.turn = state.next_player(player);
+    state.curtain = state.rules.fog;
+
+    if state.rules.mode == GameMode::Morris {
+        check_morris_draw(state);
+    } else if state.is_full() {
+        // Filling the board without a line is a win for Chaos
+        if state.rules.mode == GameMode::OrderChaos {
+            state.winner = Some(Role::Chaos.player());
+        } else {
+            state.draw = true;
+        }
+    }
+}
+
+/// Applies the Morris end conditions for the side now to move: a blocked
+/// player loses, and repeated positions or too many slides are a draw.
+fn check_morris_draw(state: &mut GameState) {
+    if state.legal_moves().is_empty() {
+        state.winner = Some(state.turn.other());
+        return;
+    }
+
+    let seen = state
+        .repetitions
+        .entry((state.board.clone(), state.turn))
+        .or_insert(0);
+    *seen += 1;
+    if *seen >= REPETITION_LIMIT {
+        state.draw = true;
+    }
+
+    // Count only slides; the placement phase uses one move per mark
+    let slides = state.moves.saturating_sub(2 * state.mark_limit());
+    if slides >= MORRIS_MOVE_LIMIT {
+        state.draw = true;
+    }
 }
 
-fn find_winning_move(
-    board: &[[Option<Player>; BOARD_SIZE]; BOARD_SIZE],
-    player: Player,
-) -> Option<(usize, usize)> {
-    for row in 0..BOARD_SIZE {
-        for col in 0..BOARD_SIZE {
-            if board[row][col].is_none() {
-                let mut test_board = *board;
-                test_board[row][col] = Some(player);
-                if check_winner(&test_board).is_some() {
-                    return Some((row, col));
-                }
+/// Drops the marks in `col` down to fill a hole left by a vanished mark.
+/// Marks never settle into blocked cells.
+fn settle_column(state: &mut GameState, col: usize) {
+    let mut target = 0;
+    for row in 0..state.board.size() {
+        if let Some(symbol) = state.board[(row, col)] {
+            while state.blocked[(target, col)] {
+                target += 1;
             }
+            if row != target {
+                state.board[(target, col)] = Some(symbol);
+                state.placed_at[(target, col)] = state.placed_at[(row, col)];
+                state.board[(row, col)] = None;
+            }
+            target += 1;
         }
     }
-    None
 }
 
-pub fn make_move(state: &mut GameState, row: usize, col: usize) {
-    let player = state.turn;
-    state.board[row][col] = Some(player);
-    state.moves += 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if let Some(line) = check_winner(&state.board) {
-        state.winner = Some(player);
-        state.winning_line = Some(line);
-    } else if state.moves == BOARD_SIZE * BOARD_SIZE {
-        // Draw
-        state.winner = None;
-    } else {
-        state.turn = player.other();
+    fn order_chaos() -> GameState {
+        let mut state = GameState {
+            rules: GameRules {
+                mode: GameMode::OrderChaos,
+                ..Default::default()
+            }
+            .normalized(),
+            ..Default::default()
+        };
+        state.reset();
+        state
+    }
+
+    #[test]
+    fn chaos_blocks_a_four_with_an_o() {
+        let mut state = order_chaos();
+        state.board[(0, 0)] = Some(Symbol::O);
+        for col in 1..=4 {
+            state.board[(0, col)] = Some(Symbol::X);
+        }
+        state.moves = 5;
+        state.turn = Player::O;
+
+        let mv = find_hard_move(&state).expect("a move");
+        assert_eq!(mv, GameMove::PlaceSymbol((0, 5), Symbol::O));
+        let mut after = state.clone();
+        make_move(&mut after, mv);
+        assert_eq!(after.winner, None);
+    }
+
+    #[test]
+    fn completing_orders_line_gives_the_win_away() {
+        let mut state = order_chaos();
+        for col in 1..=4 {
+            state.board[(0, col)] = Some(Symbol::X);
+        }
+        state.turn = Player::O;
+        assert!(gives_opponent_win(
+            &state,
+            GameMove::PlaceSymbol((0, 0), Symbol::X)
+        ));
+    }
+
+    #[test]
+    fn hard_takes_a_win_and_blocks_in_classic() {
+        let mut state = GameState::default();
+        state.reset();
+        state.board[(0, 0)] = Some(Symbol::X);
+        state.board[(0, 1)] = Some(Symbol::X);
+        state.board[(1, 1)] = Some(Symbol::O);
+        state.moves = 3;
+        state.turn = Player::O;
+        assert_eq!(find_hard_move(&state), Some(GameMove::Place((0, 2))));
+
+        // X still threatens the top row, but O can finish the middle one
+        state.board[(1, 0)] = Some(Symbol::O);
+        state.board[(2, 2)] = Some(Symbol::X);
+        state.moves = 5;
+        assert_eq!(find_hard_move(&state), Some(GameMove::Place((1, 2))));
     }
 }
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::game::components::*;
+use crate::game::solver::{self, Outcome};
+use crate::game::state::*;
+use crate::game::utils::*;
+
+/// Toggles the analysis overlay with A. Each new position is solved on the
+/// `AsyncComputeTaskPool`; the cells are coloured once the solver is done.
+pub fn update_analysis(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    markers: Query<Entity, With<AnalysisMarker>>,
+    mut texts: Query<&mut Text, With<AnalysisText>>,
+    state: Res<GameState>,
+    mut overlay: ResMut<AnalysisOverlay>,
+) {
+    let toggled = keys.just_pressed(KeyCode::KeyA);
+    if toggled {
+        overlay.enabled = !overlay.enabled;
+    }
+
+    let position = overlay.enabled.then(|| PositionKey::of(&state));
+    if toggled || overlay.position != position {
+        for e in markers.iter() {
+            commands.entity(e).despawn();
+        }
+        overlay.position = position;
+        overlay.cancel();
+        overlay.status = if !overlay.enabled {
+            ""
+        } else if !solver::supports(&state) {
+            "Analysis (A): not available for these rules"
+        } else if state.is_over() || state.setup_phase {
+            "Analysis (A): on"
+        } else {
+            let snapshot = state.clone();
+            let cancel = Arc::new(AtomicBool::new(false));
+            overlay.cancel = cancel.clone();
+            overlay.task = Some(
+                AsyncComputeTaskPool::get()
+                    .spawn(async move { solver::analyse(&snapshot, &cancel) }),
+            );
+            "Analysis (A): solving..."
+        };
+    }
+
+    if let Some(task) = overlay.task.as_mut() {
+        if let Some(values) = block_on(poll_once(task)) {
+            overlay.task = None;
+            overlay.status = match values {
+                Some(values) => {
+                    spawn_cell_values(&mut commands, &state, &values);
+                    "Analysis (A): Win, Draw or Loss in N plies"
+                }
+                None => "Analysis (A): too many positions to solve yet",
+            };
+        }
+    }
+
+    for mut text in texts.iter_mut() {
+        if **text != overlay.status {
+            **text = overlay.status.to_string();
+        }
+    }
+}
+
+fn spawn_cell_values(commands: &mut Commands, state: &GameState, values: &[solver::CellValue]) {
+    let layouts = board_layouts(state);
+    for value in values {
+        let Some(layout) = layouts.get(value.board) else {
+            continue;
+        };
+        let (row, col) = value.cell;
+        let center = layout.cell_center(row, col);
+        let cell = layout.cell_size();
+        let (color, plies) = match value.outcome {
+            Outcome::Win(plies) => (ANALYSIS_WIN, plies),
+            Outcome::Draw(plies) => (ANALYSIS_DRAW, plies),
+            Outcome::Loss(plies) => (ANALYSIS_LOSS, plies),
+        };
+        // Results close at hand are shaded more strongly
+        let alpha = 0.45 - 0.03 * plies.min(10) as f32;
+        commands.spawn((
+            Sprite::from_color(
+                color.with_alpha(alpha),
+                Vec2::splat(cell - LINE_THICKNESS * 2.0),
+            ),
+            Transform::from_translation(Vec3::new(center.x, center.y, 0.1)),
+            AnalysisMarker,
+        ));
+        commands.spawn((
+            Text2d::new(value.outcome.label()),
+            TextFont {
+                font_size: cell * 0.22,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_translation(Vec3::new(center.x, center.y, 0.12)),
+            AnalysisMarker,
+        ));
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use crate::game::coach;
+use crate::game::components::*;
+use crate::game::state::*;
+use crate::game::systems::hint_cells;
+
+type CoachButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static CoachButton,
+        &'static mut BackgroundColor,
+    ),
+    Changed<Interaction>,
+>;
+
+type CoachEntitiesQuery<'w, 's> = Query<'w, 's, Entity, Or<(With<CoachPanel>, With<CoachMarker>)>>;
+
+/// Plays the held move with the confirm button or Enter, or takes it back
+/// with the other button or Backspace.
+pub fn handle_coach_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut buttons: CoachButtonQuery,
+    mut state: ResMut<GameState>,
+    mut score: ResMut<Score>,
+) {
+    let mut action = None;
+    for (interaction, button, mut bg_color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => action = Some(*button),
+            Interaction::Hovered => *bg_color = BackgroundColor(UI_BORDER),
+            Interaction::None => *bg_color = BackgroundColor(UI_BG),
+        }
+    }
+    if state.held.is_none() {
+        return;
+    }
+    if keys.just_pressed(KeyCode::Enter) {
+        action = Some(CoachButton::Confirm);
+    } else if keys.just_pressed(KeyCode::Backspace) {
+        action = Some(CoachButton::TakeBack);
+    }
+
+    match action {
+        Some(CoachButton::Confirm) => coach::confirm(&mut state),
+        Some(CoachButton::TakeBack) => {
+            state.held = None;
+            score.coach_take_backs += 1;
+        }
+        None => {}
+    }
+}
+
+/// Shows the coach's warning while a move is held, counting each one.
+pub fn show_coach_prompt(
+    mut commands: Commands,
+    state: Res<GameState>,
+    existing: CoachEntitiesQuery,
+    mut score: ResMut<Score>,
+    mut was_held: Local<bool>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    for e in existing.iter() {
+        commands.entity(e).despawn();
+    }
+    let Some(held) = &state.held else {
+        *was_held = false;
+        return;
+    };
+    if !*was_held {
+        *was_held = true;
+        score.coach_warnings += 1;
+    }
+
+    for (layout, (row, col)) in hint_cells(&state, held.mv) {
+        let center = layout.cell_center(row, col);
+        commands.spawn((
+            Sprite::from_color(
+                ANALYSIS_LOSS.with_alpha(0.35),
+                Vec2::splat(layout.cell_size() - LINE_THICKNESS * 2.0),
+            ),
+            Transform::from_translation(Vec3::new(center.x, center.y, 0.15)),
+            CoachMarker,
+        ));
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(UI_BG),
+            CoachPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("Coach: careful, {}", held.warning)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+                TextLayout::new_with_justify(Justify::Center),
+            ));
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for (button, label) in [
+                        (CoachButton::Confirm, "Play it (Enter)"),
+                        (CoachButton::TakeBack, "Take back (Backspace)"),
+                    ] {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(220.0),
+                                height: Val::Px(36.0),
+                                margin: UiRect::horizontal(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(UI_BG),
+                            button,
+                        ))
+                        .with_children(|p| {
+                            p.spawn((
+                                Text::new(label),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                TextColor(UI_ACCENT),
+                            ));
+                        });
+                    }
+                });
+        });
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::game::components::*;
+use crate::game::fog;
+use crate::game::state::*;
+
+type ReadyButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<ReadyButton>),
+>;
+
+pub fn show_curtain(
+    mut commands: Commands,
+    state: Res<GameState>,
+    player_config: Res<PlayerConfig>,
+    existing: Query<Entity, With<FogCurtain>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+    if !fog::curtain_shown(&state, &player_config) {
+        return;
+    }
+
+    let player = state.turn;
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(BG_COLOR),
+            FogCurtain,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("Player {:?}'s turn", player)),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(player.color()),
+                Node {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                Text::new(format!("Pass the device to Player {:?}", player)),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+            ));
+
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(50.0),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(UI_BG),
+                    ReadyButton,
+                ))
+                .with_children(|p| {
+                    p.spawn((
+                        Text::new("Ready (Space)"),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(UI_ACCENT),
+                    ));
+                });
+        });
+}
+
+pub fn handle_curtain(
+    mut interaction_query: ReadyButtonQuery,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<GameState>,
+    player_config: Res<PlayerConfig>,
+) {
+    let shown = fog::curtain_shown(&state, &player_config);
+    if shown && keys.just_pressed(KeyCode::Space) {
+        state.curtain = false;
+    }
+
+    for (interaction, mut bg_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                if shown {
+                    state.curtain = false;
+                }
+            }
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+}
@@ -1,16 +1,77 @@
 use bevy::prelude::*;
 
+use crate::game::coach;
 use crate::game::components::*;
+use crate::game::fog;
+use crate::game::setup;
 use crate::game::state::*;
 use crate::game::utils::*;
 
-use super::ai::make_move;
+use super::numerical::chosen_number;
 
-pub fn spawn_grid(mut commands: Commands) {
-    let board_px = CELL_SIZE * BOARD_SIZE as f32;
+pub fn start_game(
+    mut state: ResMut<GameState>,
+    mut selection: ResMut<MoveSelection>,
+    rules: Res<GameRules>,
+    mut rng: ResMut<GameRng>,
+) {
+    state.rules = rules.normalized();
+    state.custom_setup = None;
+    state.restart(rng.next_seed());
+    selection.selected = None;
+    selection.number = None;
+}
+
+pub fn spawn_grid(
+    mut commands: Commands,
+    state: Res<GameState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (layer, layout) in board_layouts(&state).into_iter().enumerate() {
+        if layout.hex {
+            spawn_hex_cells(&mut commands, layout, &mut meshes, &mut materials);
+        } else {
+            spawn_board_lines(&mut commands, layout);
+        }
+
+        if state.rules.mode == GameMode::Qubic {
+            commands.spawn((
+                Text2d::new(format!("Layer {}", layer + 1)),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+                Transform::from_translation(Vec3::new(
+                    layout.center.x,
+                    layout.center.y + layout.board_px / 2.0 + 20.0,
+                    0.0,
+                )),
+                Grid,
+            ));
+        }
+    }
+
+    // No need to spawn per-cell entities; clicks are mapped to board indices by math.
+
+    if state.rules.gravity {
+        let layout = BoardLayout::main(state.board.size());
+        commands.spawn((
+            Sprite::from_color(GRID_GLOW, Vec2::new(layout.cell_size(), layout.board_px)),
+            Transform::from_translation(Vec3::new(0.0, 0.0, -0.2)),
+            Visibility::Hidden,
+            ColumnHover,
+        ));
+    }
+}
+
+fn spawn_board_lines(commands: &mut Commands, layout: BoardLayout) {
+    let board_px = layout.board_px;
     let half = board_px / 2.0;
-    for i in 1..BOARD_SIZE {
-        let offset = i as f32 * CELL_SIZE - half;
+    let (cx, cy) = (layout.center.x, layout.center.y);
+    for i in 1..layout.size {
+        let offset = i as f32 * layout.cell_size() - half;
 
         // Vertical line glow (underneath)
         commands.spawn((
@@ -18,7 +79,7 @@ pub fn spawn_grid(mut commands: Commands) {
                 GRID_GLOW,
                 Vec2::new(LINE_THICKNESS + 4.0, board_px + LINE_THICKNESS + 4.0),
             ),
-            Transform::from_translation(Vec3::new(offset, 0.0, -0.1)),
+            Transform::from_translation(Vec3::new(cx + offset, cy, -0.1)),
             Grid,
         ));
 
@@ -28,7 +89,7 @@ pub fn spawn_grid(mut commands: Commands) {
                 GRID_COLOR,
                 Vec2::new(LINE_THICKNESS, board_px + LINE_THICKNESS),
             ),
-            Transform::from_translation(Vec3::new(offset, 0.0, 0.0)),
+            Transform::from_translation(Vec3::new(cx + offset, cy, 0.0)),
             Grid,
         ));
 
@@ -38,7 +99,7 @@ pub fn spawn_grid(mut commands: Commands) {
                 GRID_GLOW,
                 Vec2::new(board_px + LINE_THICKNESS + 4.0, LINE_THICKNESS + 4.0),
             ),
-            Transform::from_translation(Vec3::new(0.0, offset, -0.1)),
+            Transform::from_translation(Vec3::new(cx, cy + offset, -0.1)),
             Grid,
         ));
 
@@ -48,26 +109,63 @@ pub fn spawn_grid(mut commands: Commands) {
                 GRID_COLOR,
                 Vec2::new(board_px + LINE_THICKNESS, LINE_THICKNESS),
             ),
-            Transform::from_translation(Vec3::new(0.0, offset, 0.0)),
+            Transform::from_translation(Vec3::new(cx, cy + offset, 0.0)),
             Grid,
         ));
     }
+}
 
-    // No need to spawn per-cell entities; clicks are mapped to board indices by math.
+/// Draws each hex cell as an outlined hexagon; neighbours share their edges.
+fn spawn_hex_cells(
+    commands: &mut Commands,
+    layout: BoardLayout,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) {
+    let radius = layout.hex_radius();
+    let glow = meshes.add(RegularPolygon::new(radius + 2.0, 6));
+    let outline = meshes.add(RegularPolygon::new(radius, 6));
+    let inner = meshes.add(RegularPolygon::new(radius - LINE_THICKNESS / 2.0, 6));
+    let glow_material = materials.add(ColorMaterial::from_color(GRID_GLOW));
+    let outline_material = materials.add(ColorMaterial::from_color(GRID_COLOR));
+    let inner_material = materials.add(ColorMaterial::from_color(BG_COLOR));
+
+    for row in 0..layout.size {
+        for col in 0..layout.size {
+            let center = layout.cell_center(row, col);
+            for (mesh, material, z) in [
+                (&glow, &glow_material, -0.1),
+                (&outline, &outline_material, 0.0),
+                (&inner, &inner_material, 0.01),
+            ] {
+                commands.spawn((
+                    Mesh2d(mesh.clone()),
+                    MeshMaterial2d(material.clone()),
+                    Transform::from_translation(center.extend(z)),
+                    Grid,
+                ));
+            }
+        }
+    }
 }
 
 pub fn handle_clicks(
     buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
     mut state: ResMut<GameState>,
-    windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform)>,
+    mut selection: ResMut<MoveSelection>,
+    cursor: WorldCursor,
     player_config: Res<PlayerConfig>,
     interaction_query: Query<&Interaction, With<Button>>,
 ) {
-    if !buttons.just_pressed(MouseButton::Left) {
+    let right_pressed =
+        state.rules.mode.free_symbol_choice() && buttons.just_pressed(MouseButton::Right);
+    let pressed = buttons.just_pressed(MouseButton::Left) || right_pressed;
+    if !pressed && !buttons.just_released(MouseButton::Left) {
         return;
     }
-    if state.winner.is_some() {
+    // A move held by the coach waits for confirm or take back
+    if state.is_over() || state.held.is_some() {
         return;
     }
 
@@ -78,33 +176,164 @@ pub fn handle_clicks(
         }
     }
 
+    // Custom setup: clicks cycle cells through blocked, X and O
+    if state.setup_phase {
+        if pressed {
+            if let Some(pos) = cursor
+                .position()
+                .and_then(|pos| main_layout(&state).cell_at(pos))
+            {
+                setup::cycle_cell(&mut state, pos);
+            }
+        }
+        return;
+    }
+
     // Check if it's a human player's turn
-    let current_player_type = match state.turn {
-        Player::X => player_config.x_type,
-        Player::O => player_config.o_type,
+    let current_player_type = player_config.type_of(state.turn);
+
+    if current_player_type != PlayerType::Human || fog::curtain_shown(&state, &player_config) {
+        return;
+    }
+
+    let Some(cursor_world) = cursor.position() else {
+        return;
     };
 
-    if current_player_type != PlayerType::Human {
+    if state.rules.mode.uses_boards() {
+        if !pressed {
+            return;
+        }
+        let mv = board_layouts(&state)
+            .iter()
+            .enumerate()
+            .find_map(|(index, layout)| {
+                layout
+                    .cell_at(cursor_world)
+                    .map(|pos| GameMove::PlaceOn(index, pos))
+            });
+        if let Some(mv) = mv.filter(|mv| state.legal_moves().contains(mv)) {
+            coach::play_or_hold(&mut state, mv);
+        }
         return;
     }
 
-    let Some(cursor_world) = world_cursor_pos(windows, camera_q) else {
+    let Some((row, col)) = main_layout(&state).cell_at(cursor_world) else {
         return;
     };
 
-    let board_px = CELL_SIZE * BOARD_SIZE as f32;
-    let half = board_px / 2.0;
-    let x = cursor_world.x + half;
-    let y = cursor_world.y + half;
-    if x < 0.0 || y < 0.0 || x >= board_px || y >= board_px {
+    if state.rules.mode == GameMode::Quantum {
+        if !pressed {
+            return;
+        }
+        let cell = (row, col);
+        // First click picks one cell of the spooky pair, second click the
+        // other; during a collapse, click one of the highlighted cells
+        let mv = if state.pending_collapse.is_some() {
+            Some(GameMove::Collapse(cell))
+        } else {
+            match selection.selected {
+                Some(first) if first == cell => {
+                    selection.selected = None;
+                    None
+                }
+                Some(first) => Some(GameMove::Spooky(first.min(cell), first.max(cell))),
+                None => Some(GameMove::Place(cell)),
+            }
+        };
+        let legal = state.legal_moves();
+        match mv {
+            Some(mv) if legal.contains(&mv) => {
+                selection.selected = None;
+                coach::play_or_hold(&mut state, mv);
+            }
+            Some(GameMove::Place(_)) | Some(GameMove::Spooky(..))
+                if state.board[cell].is_none() && state.pending_collapse.is_none() =>
+            {
+                selection.selected = Some(cell);
+            }
+            _ => {}
+        }
         return;
     }
-    let col = (x / CELL_SIZE).floor() as usize;
-    let row = (y / CELL_SIZE).floor() as usize;
 
-    if state.board[row][col].is_some() {
+    if state.in_movement_phase() {
+        // Click-select-then-click-target or drag-and-drop: pressing on an own
+        // mark selects it, pressing or releasing on a legal destination slides it
+        let slide = selection
+            .selected
+            .map(|from| GameMove::Slide {
+                from,
+                to: (row, col),
+            })
+            .filter(|mv| state.legal_moves().contains(mv));
+        if let Some(mv) = slide {
+            selection.selected = None;
+            coach::play_or_hold(&mut state, mv);
+        } else if pressed {
+            selection.selected =
+                (state.board[(row, col)] == Some(state.turn.symbol())).then_some((row, col));
+        }
         return;
     }
 
-    make_move(&mut state, row, col);
+    if !pressed {
+        return;
+    }
+
+    // Wild and Order and Chaos: left click places X, right click or
+    // Shift+click places O;
+    // Numerical mode places the number chosen in the picker
+    let symbol = if state.rules.mode == GameMode::Numerical {
+        let Some(number) = chosen_number(&state, &selection) else {
+            return;
+        };
+        Symbol::Number(number)
+    } else if right_pressed || keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        Symbol::O
+    } else {
+        Symbol::X
+    };
+
+    // With gravity any cell selects its column; the mark drops to the bottom
+    let gravity = state.rules.gravity;
+    let mv = state.legal_moves().into_iter().find(|mv| {
+        let target = mv.target();
+        let on_target = target == (row, col) || (gravity && target.1 == col);
+        match mv {
+            GameMove::PlaceSymbol(_, s) => on_target && *s == symbol,
+            _ => on_target,
+        }
+    });
+    if let Some(mv) = mv {
+        coach::play_or_hold(&mut state, mv);
+    }
+}
+
+pub fn update_column_hover(
+    state: Res<GameState>,
+    cursor: WorldCursor,
+    player_config: Res<PlayerConfig>,
+    mut hover: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<ColumnHover>>,
+) {
+    let Ok((mut transform, mut sprite, mut visibility)) = hover.single_mut() else {
+        return;
+    };
+
+    let current_player_type = player_config.type_of(state.turn);
+    let layout = main_layout(&state);
+    let column = cursor
+        .position()
+        .and_then(|pos| layout.cell_at(pos))
+        .map(|(_, col)| col)
+        .filter(|&col| state.landing_row(col).is_some());
+
+    match column {
+        Some(col) if current_player_type == PlayerType::Human && !state.is_over() => {
+            transform.translation.x = layout.cell_center(0, col).x;
+            sprite.color = state.turn.glow();
+            *visibility = Visibility::Visible;
+        }
+        _ => *visibility = Visibility::Hidden,
+    }
 }
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 use crate::game::components::*;
+use crate::game::solver;
 use crate::game::state::*;
 
 // Type aliases to reduce complexity
@@ -12,7 +13,10 @@ type GameOverButtonQuery<'w, 's> = Query<
         Option<&'static BackToMenuButton>,
         &'static mut BackgroundColor,
     ),
-    (Changed<Interaction>, With<Button>),
+    (
+        Changed<Interaction>,
+        Or<(With<NewGameButton>, With<BackToMenuButton>)>,
+    ),
 >;
 
 type CleanupEntitiesQuery<'w, 's> = Query<
@@ -23,20 +27,42 @@ type CleanupEntitiesQuery<'w, 's> = Query<
         With<Mark>,
         With<WinHighlight>,
         With<Grid>,
+        With<ColumnHover>,
+        With<MoveHint>,
+        With<CollapsePrompt>,
+        With<NumberPicker>,
+        With<SetupPrompt>,
+        With<FogCurtain>,
+        With<RoundPrompt>,
+        With<HintMarker>,
+        With<AnalysisMarker>,
+        Or<(
+            With<ReviewPanel>,
+            With<ReviewMarker>,
+            With<CoachPanel>,
+            With<CoachMarker>,
+        )>,
         With<ScoreboardUI>,
         With<GameOverUI>,
     )>,
 >;
 
 pub fn update_score(mut score: ResMut<Score>, state: Res<GameState>, mut game_ended: Local<bool>) {
-    if state.is_changed() && (state.winner.is_some() || state.moves == BOARD_SIZE * BOARD_SIZE) {
+    if state.is_changed() && state.is_over() {
         if !*game_ended {
             *game_ended = true;
             match state.winner {
                 Some(Player::X) => score.x_wins += 1,
                 Some(Player::O) => score.o_wins += 1,
+                Some(Player::Triangle) => score.triangle_wins += 1,
                 None => score.draws += 1,
             }
+            match state.runner_up {
+                Some(Player::X) => score.x_half_points += 1,
+                Some(Player::O) => score.o_half_points += 1,
+                // Half points only occur in Quantum, a two-player mode
+                Some(Player::Triangle) | None => {}
+            }
         }
     } else if state.moves == 0 {
         *game_ended = false;
@@ -58,11 +84,11 @@ pub fn show_game_over_ui(
     }
 
     // Show game over UI if game ended
-    if state.winner.is_some() || state.moves == BOARD_SIZE * BOARD_SIZE {
-        let message = match state.winner {
-            Some(Player::X) => "Player X Wins!",
-            Some(Player::O) => "Player O Wins!",
-            None => "It's a Draw!",
+    if state.is_over() {
+        let message = match (state.winner, state.winner.and_then(|p| state.role(p))) {
+            (Some(_), Some(role)) => format!("{} Wins!", role.label()),
+            (Some(player), None) => format!("Player {:?} Wins!", player),
+            (None, _) => "It's a Draw!".to_string(),
         };
 
         commands
@@ -94,6 +120,64 @@ pub fn show_game_over_ui(
                     },
                 ));
 
+                // How the role was fulfilled in Order and Chaos
+                if let Some(role) = state.winner.and_then(|p| state.role(p)) {
+                    let detail = match role {
+                        Role::Order => "Player X made five in a row",
+                        Role::Chaos => "Player O filled the board without five in a row",
+                    };
+                    parent.spawn((
+                        Text::new(detail),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(UI_ACCENT),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(20.0)),
+                            ..default()
+                        },
+                    ));
+                }
+
+                // A computer player that failed to move
+                if let Some(player) = state.forfeited {
+                    parent.spawn((
+                        Text::new(format!(
+                            "Player {:?} forfeited: its computer gave no legal move",
+                            player
+                        )),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(UI_ACCENT),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(20.0)),
+                            ..default()
+                        },
+                    ));
+                }
+
+                // Simultaneous lines in Quantum mode
+                if let Some(runner_up) = state.runner_up {
+                    parent.spawn((
+                        Text::new(format!(
+                            "Player {:?} also completed a line and scores 1/2",
+                            runner_up
+                        )),
+                        TextFont {
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(UI_ACCENT),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(20.0)),
+                            ..default()
+                        },
+                    ));
+                }
+
                 parent
                     .spawn((
                         Button,
@@ -106,6 +190,7 @@ pub fn show_game_over_ui(
                             ..default()
                         },
                         BackgroundColor(UI_BG),
+                        NewGameButton,
                     ))
                     .with_children(|parent| {
                         parent.spawn((
@@ -118,6 +203,34 @@ pub fn show_game_over_ui(
                         ));
                     });
 
+                // Step through the game with every move graded by the solver
+                if solver::supports(&state) && !state.history.is_empty() {
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(200.0),
+                                height: Val::Px(50.0),
+                                margin: UiRect::all(Val::Px(10.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(UI_BG),
+                            AnalyseButton,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new("Analyse"),
+                                TextFont {
+                                    font_size: 20.0,
+                                    ..default()
+                                },
+                                TextColor(UI_ACCENT),
+                            ));
+                        });
+                }
+
                 parent
                     .spawn((
                         Button,
@@ -149,6 +262,7 @@ pub fn show_game_over_ui(
 pub fn handle_game_over_buttons(
     mut interaction_query: GameOverButtonQuery,
     mut state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     for (interaction, back_to_menu, mut bg_color) in &mut interaction_query {
@@ -157,7 +271,7 @@ pub fn handle_game_over_buttons(
                 if back_to_menu.is_some() {
                     next_state.set(AppState::Menu);
                 } else {
-                    state.reset();
+                    state.restart(rng.next_seed());
                 }
             }
             Interaction::Hovered => {
@@ -173,11 +287,12 @@ pub fn handle_game_over_buttons(
 pub fn keyboard_controls(
     keys: Res<ButtonInput<KeyCode>>,
     mut state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
     mut next_state: ResMut<NextState<AppState>>,
     current_state: Res<State<AppState>>,
 ) {
     if keys.just_pressed(KeyCode::KeyR) {
-        state.reset();
+        state.restart(rng.next_seed());
     }
 
     if keys.just_pressed(KeyCode::Escape) && *current_state.get() == AppState::Playing {
@@ -190,7 +305,16 @@ pub fn cleanup_game(
     entities: CleanupEntitiesQuery,
     mut state: ResMut<GameState>,
     mut score: ResMut<Score>,
+    mut thinking: ResMut<ComputerThinking>,
+    mut hints: ResMut<HintState>,
+    mut overlay: ResMut<AnalysisOverlay>,
 ) {
+    // Leaving to the menu cancels any computer move, hint or analysis in
+    // progress; the overlay stays on for the next game
+    thinking.pending = None;
+    *hints = HintState::default();
+    overlay.cancel();
+    overlay.position = None;
     for e in entities.iter() {
         commands.entity(e).despawn();
     }
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once};
+
+use crate::game::components::*;
+use crate::game::fog;
+use crate::game::hint::{self, HintRecord};
+use crate::game::state::*;
+use crate::game::strategy::StrategyRegistry;
+use crate::game::systems::make_move;
+use crate::game::utils::*;
+
+type HintButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<HintButton>),
+>;
+
+/// Asks the strongest strategy for a move when the human to move presses H
+/// or the hint button, as long as they have hints left.
+pub fn request_hint(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut buttons: HintButtonQuery,
+    state: Res<GameState>,
+    player_config: Res<PlayerConfig>,
+    registry: Res<StrategyRegistry>,
+    mut hints: ResMut<HintState>,
+) {
+    let mut clicked = false;
+    for (interaction, mut bg_color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => clicked = true,
+            Interaction::Hovered => *bg_color = BackgroundColor(UI_BORDER),
+            Interaction::None => *bg_color = BackgroundColor(UI_BG),
+        }
+    }
+    if !clicked && !keys.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+
+    let player = state.turn;
+    if state.is_over()
+        || state.setup_phase
+        || player_config.type_of(player) != PlayerType::Human
+        || fog::curtain_shown(&state, &player_config)
+        || hints.pending.is_some()
+        || hints.shown.is_some()
+        || hints_left(&state, player) == Some(0)
+    {
+        return;
+    }
+    let Some((name, strategy)) = hint::strongest(&registry) else {
+        return;
+    };
+
+    // The hint only knows what the player sees
+    let view = fog::view_for(&state, player);
+    hints.pending = Some(PendingMove::spawn(
+        Some(strategy),
+        name,
+        view,
+        player,
+        &state,
+    ));
+}
+
+/// Collects the finished hint and highlights its cells until a move is made.
+/// A hint counts against the player's limit, and goes into the game's
+/// record, once it is shown.
+pub fn show_hint(
+    mut commands: Commands,
+    markers: Query<Entity, With<HintMarker>>,
+    mut texts: Query<&mut Text, With<HintText>>,
+    mut state: ResMut<GameState>,
+    mut hints: ResMut<HintState>,
+    mut score: ResMut<Score>,
+) {
+    if hints.pending.as_ref().is_some_and(|p| !p.is_for(&state)) {
+        hints.pending = None;
+    }
+    if hints
+        .shown
+        .as_ref()
+        .is_some_and(|h| h.position != PositionKey::of(&state) || state.is_over())
+    {
+        hints.shown = None;
+    }
+
+    if let Some(pending) = hints.pending.as_mut() {
+        if let Some(choice) = block_on(poll_once(&mut pending.task)) {
+            hints.pending = None;
+            let view = fog::view_for(&state, state.turn);
+            hints.shown = choice
+                .filter(|choice| state.legal_moves().contains(&choice.mv))
+                .map(|choice| ShownHint {
+                    mv: choice.mv,
+                    reason: hint::reason(&view, &choice),
+                    position: PositionKey::of(&state),
+                });
+            if let Some(shown) = &hints.shown {
+                let player = state.turn;
+                let record = HintRecord {
+                    ply: state.history.len(),
+                    player,
+                    mv: shown.mv,
+                };
+                state.hints.push(record);
+                score.record_hint(player);
+            }
+        }
+    }
+
+    let text = match (&hints.pending, &hints.shown) {
+        (Some(_), _) => "Looking for a hint...".to_string(),
+        (None, Some(shown)) => format!("Hint: {}", shown.reason),
+        (None, None) => String::new(),
+    };
+    for mut label in texts.iter_mut() {
+        if **label != text {
+            **label = text.clone();
+        }
+    }
+
+    if !hints.is_changed() {
+        return;
+    }
+    for e in markers.iter() {
+        commands.entity(e).despawn();
+    }
+    let Some(shown) = &hints.shown else {
+        return;
+    };
+    for (layout, (row, col)) in hint_cells(&state, shown.mv) {
+        let center = layout.cell_center(row, col);
+        commands.spawn((
+            Sprite::from_color(
+                HINT_COLOR,
+                Vec2::splat(layout.cell_size() - LINE_THICKNESS * 2.0),
+            ),
+            Transform::from_translation(Vec3::new(center.x, center.y, 0.15)),
+            HintMarker,
+        ));
+    }
+}
+
+/// Shows how many hints the human to move has left; hidden when hints are
+/// off or no human is playing.
+pub fn update_hint_button(
+    state: Res<GameState>,
+    player_config: Res<PlayerConfig>,
+    mut buttons: Query<&mut Node, With<HintButton>>,
+    mut labels: Query<&mut Text, With<HintButtonText>>,
+) {
+    if !state.is_changed() && !player_config.is_changed() {
+        return;
+    }
+
+    let human = std::iter::once(state.turn)
+        .chain(state.players())
+        .find(|&p| player_config.type_of(p) == PlayerType::Human);
+    let display = match human {
+        Some(_) if state.rules.hint_limit != Some(0) => Display::Flex,
+        _ => Display::None,
+    };
+    for mut node in buttons.iter_mut() {
+        node.display = display;
+    }
+
+    let label = match human.and_then(|p| hints_left(&state, p)) {
+        Some(left) => format!("Hint (H): {left} left"),
+        None => "Hint (H)".to_string(),
+    };
+    for mut text in labels.iter_mut() {
+        **text = label.clone();
+    }
+}
+
+/// Hints `player` may still ask for this game; `None` when unlimited.
+fn hints_left(state: &GameState, player: Player) -> Option<u32> {
+    state
+        .rules
+        .hint_limit
+        .map(|limit| limit.saturating_sub(state.hints_used(player)))
+}
+
+/// Cells to highlight for `mv`, with the layout of the board they are on.
+pub fn hint_cells(state: &GameState, mv: GameMove) -> Vec<(BoardLayout, (usize, usize))> {
+    let main = main_layout(state);
+    match mv {
+        GameMove::PlaceOn(index, pos) => board_layouts(state)
+            .get(index)
+            .map(|&layout| vec![(layout, pos)])
+            .unwrap_or_default(),
+        GameMove::Slide { from, to } => vec![(main, from), (main, to)],
+        GameMove::Spooky(a, b) => vec![(main, a), (main, b)],
+        // With gravity the mark lands lower down the column
+        _ => {
+            let mut after = state.clone();
+            make_move(&mut after, mv);
+            vec![(main, after.last_move.unwrap_or(mv.target()))]
+        }
+    }
+}
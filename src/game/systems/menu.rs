@@ -1,25 +1,67 @@
 use bevy::prelude::*;
 
 use crate::game::components::*;
+use crate::game::difficulty;
 use crate::game::state::*;
+use crate::game::strategy::StrategyRegistry;
 
 // Type alias to reduce complexity
-type MenuButtonQuery<'w, 's> = Query<
+type PlayerSelectQuery<'w, 's> = Query<
     'w,
     's,
     (
         &'static Interaction,
-        &'static MenuButton,
+        &'static PlayerSelectButton,
         &'static mut BackgroundColor,
     ),
     (Changed<Interaction>, With<Button>),
 >;
 
-pub fn spawn_menu(mut commands: Commands) {
+type DifficultyButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static DifficultyButton,
+        &'static mut BackgroundColor,
+    ),
+    Changed<Interaction>,
+>;
+
+type StartGameQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<StartGameButton>),
+>;
+
+type OptionButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static OptionButton,
+        &'static mut BackgroundColor,
+    ),
+    (Changed<Interaction>, With<Button>),
+>;
+
+const OPTION_ROWS: [&[MenuOption]; 4] = [
+    &[
+        MenuOption::BoardSize,
+        MenuOption::WinLength,
+        MenuOption::BoardCount,
+    ],
+    &[MenuOption::Gravity, MenuOption::Hex, MenuOption::Wrap],
+    &[MenuOption::Setup, MenuOption::Hints, MenuOption::Coach],
+    &[MenuOption::Players, MenuOption::Fog],
+];
+
+pub fn spawn_menu(mut commands: Commands, rules: Res<GameRules>, player_config: Res<PlayerConfig>) {
     let button_style = Node {
         width: Val::Px(300.0),
-        height: Val::Px(60.0),
-        margin: UiRect::all(Val::Px(10.0)),
+        height: Val::Px(50.0),
+        margin: UiRect::axes(Val::Px(10.0), Val::Px(8.0)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -52,112 +94,281 @@ pub fn spawn_menu(mut commands: Commands) {
                 },
                 TextColor(UI_ACCENT),
                 Node {
-                    margin: UiRect::all(Val::Px(30.0)),
+                    margin: UiRect::all(Val::Px(10.0)),
                     ..default()
                 },
             ));
 
-            parent.spawn((
-                Text::new("Choose Players:"),
-                TextFont {
-                    font_size: 30.0,
-                    ..default()
-                },
-                TextColor(UI_ACCENT),
-                Node {
-                    margin: UiRect::bottom(Val::Px(20.0)),
-                    ..default()
-                },
-            ));
-
-            // Human vs Human
+            // Match options (click to cycle)
             parent
                 .spawn((
                     Button,
                     button_style.clone(),
                     BackgroundColor(UI_BG),
-                    MenuButton {
-                        x_type: PlayerType::Human,
-                        o_type: PlayerType::Human,
-                    },
+                    OptionButton(MenuOption::Mode),
                 ))
                 .with_children(|p| {
                     p.spawn((
-                        Text::new("Human vs Human"),
+                        Text::new(option_label(&rules, MenuOption::Mode)),
                         text_style.clone(),
-                        TextColor(UI_ACCENT),
+                        TextColor(WIN_COLOR),
+                        OptionText(MenuOption::Mode),
                     ));
                 });
 
-            // Human vs Computer Easy
+            for options in OPTION_ROWS {
+                parent
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        margin: UiRect::bottom(Val::Px(10.0)),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        for &option in options {
+                            row.spawn((
+                                Button,
+                                Node {
+                                    // A lone option spans the row
+                                    width: Val::Px(if options.len() == 1 { 310.0 } else { 150.0 }),
+                                    height: Val::Px(40.0),
+                                    margin: UiRect::horizontal(Val::Px(5.0)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                BackgroundColor(UI_BG),
+                                OptionButton(option),
+                            ))
+                            .with_children(|p| {
+                                p.spawn((
+                                    Text::new(option_label(&rules, option)),
+                                    TextFont {
+                                        font_size: 16.0,
+                                        ..default()
+                                    },
+                                    TextColor(WIN_COLOR),
+                                    OptionText(option),
+                                ));
+                            });
+                        }
+                    });
+            }
+
+            parent.spawn((
+                Text::new("Choose Players:"),
+                TextFont {
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+            ));
+
+            // Who plays each side: a human or any registered strategy
             parent
-                .spawn((
-                    Button,
-                    button_style.clone(),
-                    BackgroundColor(UI_BG),
-                    MenuButton {
-                        x_type: PlayerType::Human,
-                        o_type: PlayerType::ComputerEasy,
-                    },
-                ))
-                .with_children(|p| {
-                    p.spawn((
-                        Text::new("Human vs Computer (Easy)"),
-                        text_style.clone(),
-                        TextColor(UI_ACCENT),
-                    ));
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for player in [Player::X, Player::O, Player::Triangle] {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(150.0),
+                                height: Val::Px(50.0),
+                                margin: UiRect::horizontal(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                display: player_display(&rules, player),
+                                ..default()
+                            },
+                            BackgroundColor(UI_BG),
+                            PlayerSelectButton(player),
+                        ))
+                        .with_children(|p| {
+                            p.spawn((
+                                Text::new(player_label(&player_config, player)),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                TextColor(player.color()),
+                                PlayerSelectText(player),
+                            ));
+                        });
+                    }
                 });
 
-            // Human vs Computer Hard
+            // Level - and + under each player set to a computer level
             parent
-                .spawn((
-                    Button,
-                    button_style.clone(),
-                    BackgroundColor(UI_BG),
-                    MenuButton {
-                        x_type: PlayerType::Human,
-                        o_type: PlayerType::ComputerHard,
-                    },
-                ))
-                .with_children(|p| {
-                    p.spawn((
-                        Text::new("Human vs Computer (Hard)"),
-                        text_style.clone(),
-                        TextColor(UI_ACCENT),
-                    ));
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for player in [Player::X, Player::O, Player::Triangle] {
+                        row.spawn((
+                            Node {
+                                width: Val::Px(150.0),
+                                margin: UiRect::horizontal(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                display: player_display(&rules, player),
+                                ..default()
+                            },
+                            difficulty_visibility(&player_config, player),
+                            DifficultyControl(player),
+                        ))
+                        .with_children(|control| {
+                            for (step, label) in [(-1, "-"), (1, "+")] {
+                                control
+                                    .spawn((
+                                        Button,
+                                        Node {
+                                            width: Val::Px(40.0),
+                                            height: Val::Px(28.0),
+                                            margin: UiRect::horizontal(Val::Px(5.0)),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        BackgroundColor(UI_BG),
+                                        DifficultyButton { player, step },
+                                    ))
+                                    .with_children(|p| {
+                                        p.spawn((
+                                            Text::new(label),
+                                            TextFont {
+                                                font_size: 18.0,
+                                                ..default()
+                                            },
+                                            TextColor(player.color()),
+                                        ));
+                                    });
+                            }
+                        });
+                    }
                 });
 
-            // Computer vs Computer
             parent
                 .spawn((
                     Button,
                     button_style,
                     BackgroundColor(UI_BG),
-                    MenuButton {
-                        x_type: PlayerType::ComputerEasy,
-                        o_type: PlayerType::ComputerHard,
-                    },
+                    StartGameButton,
                 ))
                 .with_children(|p| {
-                    p.spawn((
-                        Text::new("Computer vs Computer"),
-                        text_style,
-                        TextColor(UI_ACCENT),
-                    ));
+                    p.spawn((Text::new("Start Game"), text_style, TextColor(UI_ACCENT)));
                 });
         });
 }
 
+/// The triangle's selector only shows when a third player is enabled.
+fn player_display(rules: &GameRules, player: Player) -> Display {
+    if player == Player::Triangle && !rules.three_players {
+        Display::None
+    } else {
+        Display::Flex
+    }
+}
+
+fn player_label(player_config: &PlayerConfig, player: Player) -> String {
+    format!(
+        "{}: {}",
+        player.letter(),
+        player_config.type_of(player).label()
+    )
+}
+
+/// The level controls only show for a computer level.
+fn difficulty_visibility(player_config: &PlayerConfig, player: Player) -> Visibility {
+    match player_config.type_of(player) {
+        PlayerType::Computer(name) if difficulty::level_of(name).is_some() => Visibility::Inherited,
+        _ => Visibility::Hidden,
+    }
+}
+
+/// Human first, then every registered strategy in turn. The computer levels
+/// take a single place, entered at the default level.
+fn next_player_type(current: PlayerType, registry: &StrategyRegistry) -> PlayerType {
+    let collapse = |player_type| match player_type {
+        PlayerType::Computer(name) if difficulty::level_of(name).is_some() => {
+            PlayerType::Computer(difficulty::level_name(difficulty::DEFAULT_LEVEL))
+        }
+        other => other,
+    };
+    let mut types: Vec<PlayerType> = std::iter::once(PlayerType::Human)
+        .chain(registry.names().map(PlayerType::Computer))
+        .map(collapse)
+        .collect();
+    types.dedup();
+    let index = types
+        .iter()
+        .position(|&t| t == collapse(current))
+        .unwrap_or(0);
+    types[(index + 1) % types.len()]
+}
+
+/// Steps a computer level player down or up, from 1 to 10.
+pub fn handle_difficulty_buttons(
+    mut buttons: DifficultyButtonQuery,
+    mut player_config: ResMut<PlayerConfig>,
+) {
+    for (interaction, button, mut bg_color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                let PlayerType::Computer(name) = player_config.type_of(button.player) else {
+                    continue;
+                };
+                let Some(level) = difficulty::level_of(name) else {
+                    continue;
+                };
+                let level = level.saturating_add_signed(button.step);
+                player_config.set_type(
+                    button.player,
+                    PlayerType::Computer(difficulty::level_name(level)),
+                );
+            }
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+}
+
 pub fn handle_menu_buttons(
-    mut interaction_query: MenuButtonQuery,
+    mut select_query: PlayerSelectQuery,
+    mut start_query: StartGameQuery,
     mut player_config: ResMut<PlayerConfig>,
+    registry: Res<StrategyRegistry>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
-    for (interaction, menu_button, mut bg_color) in &mut interaction_query {
+    for (interaction, select, mut bg_color) in &mut select_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let next = next_player_type(player_config.type_of(select.0), &registry);
+                player_config.set_type(select.0, next);
+            }
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+
+    for (interaction, mut bg_color) in &mut start_query {
         match *interaction {
             Interaction::Pressed => {
-                player_config.x_type = menu_button.x_type;
-                player_config.o_type = menu_button.o_type;
                 next_state.set(AppState::Playing);
             }
             Interaction::Hovered => {
@@ -170,6 +381,134 @@ pub fn handle_menu_buttons(
     }
 }
 
+pub fn handle_option_buttons(
+    mut interaction_query: OptionButtonQuery,
+    mut rules: ResMut<GameRules>,
+) {
+    for (interaction, option_button, mut bg_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                cycle_option(&mut rules, option_button.0);
+            }
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+}
+
+pub fn update_option_labels(rules: Res<GameRules>, mut query: Query<(&mut Text, &OptionText)>) {
+    if !rules.is_changed() {
+        return;
+    }
+
+    for (mut text, option_text) in query.iter_mut() {
+        **text = option_label(&rules, option_text.0);
+    }
+}
+
+pub fn update_player_labels(
+    rules: Res<GameRules>,
+    player_config: Res<PlayerConfig>,
+    mut texts: Query<(&mut Text, &PlayerSelectText)>,
+    mut buttons: Query<(&mut Node, &PlayerSelectButton)>,
+    mut controls: Query<
+        (&mut Node, &mut Visibility, &DifficultyControl),
+        Without<PlayerSelectButton>,
+    >,
+) {
+    if !rules.is_changed() && !player_config.is_changed() {
+        return;
+    }
+
+    for (mut text, select) in texts.iter_mut() {
+        **text = player_label(&player_config, select.0);
+    }
+    for (mut node, select) in buttons.iter_mut() {
+        node.display = player_display(&rules, select.0);
+    }
+    for (mut node, mut visibility, control) in controls.iter_mut() {
+        node.display = player_display(&rules, control.0);
+        *visibility = difficulty_visibility(&player_config, control.0);
+    }
+}
+
+fn option_label(rules: &GameRules, option: MenuOption) -> String {
+    match option {
+        MenuOption::Mode => format!("Mode: {}", rules.mode.label()),
+        MenuOption::BoardSize => format!("Board: {0}x{0}", rules.board_size),
+        MenuOption::WinLength => format!("Win: {} in a row", rules.win_length),
+        MenuOption::Gravity => format!("Gravity: {}", if rules.gravity { "On" } else { "Off" }),
+        MenuOption::BoardCount => format!("Boards: {}", rules.board_count),
+        MenuOption::Hex => format!("Grid: {}", if rules.hex { "Hex" } else { "Square" }),
+        MenuOption::Wrap => format!("Wrap: {}", if rules.wrap { "On" } else { "Off" }),
+        MenuOption::Setup => format!("Setup: {}", rules.setup.label()),
+        MenuOption::Players if rules.three_players => {
+            let order: Vec<_> = rules.turn_order.iter().map(|p| p.letter()).collect();
+            format!("Players: {}", order.join("-"))
+        }
+        MenuOption::Players => "Players: 2".to_string(),
+        MenuOption::Fog => format!("Fog: {}", if rules.fog { "On" } else { "Off" }),
+        MenuOption::Hints => match rules.hint_limit {
+            Some(0) => "Hints: Off".to_string(),
+            Some(limit) => format!("Hints: {limit}"),
+            None => "Hints: Unlimited".to_string(),
+        },
+        MenuOption::Coach => format!("Coach: {}", if rules.coach { "On" } else { "Off" }),
+    }
+}
+
+fn cycle_option(rules: &mut GameRules, option: MenuOption) {
+    match option {
+        MenuOption::Mode => rules.mode = rules.mode.next(),
+        MenuOption::BoardSize => {
+            rules.board_size = if rules.board_size >= MAX_BOARD_SIZE {
+                DEFAULT_BOARD_SIZE
+            } else {
+                rules.board_size + 1
+            };
+            rules.win_length = rules.win_length.min(rules.board_size);
+        }
+        MenuOption::WinLength => {
+            rules.win_length = if rules.win_length >= rules.board_size {
+                DEFAULT_BOARD_SIZE
+            } else {
+                rules.win_length + 1
+            };
+        }
+        MenuOption::Gravity => rules.gravity = !rules.gravity,
+        MenuOption::Hex => rules.hex = !rules.hex,
+        MenuOption::Wrap => rules.wrap = !rules.wrap,
+        MenuOption::Fog => rules.fog = !rules.fog,
+        MenuOption::Coach => rules.coach = !rules.coach,
+        MenuOption::Setup => rules.setup = rules.setup.next(),
+        MenuOption::Hints => {
+            let index = HINT_LIMITS.iter().position(|&l| l == rules.hint_limit);
+            rules.hint_limit = HINT_LIMITS[index.map_or(0, |i| (i + 1) % HINT_LIMITS.len())];
+        }
+        MenuOption::BoardCount => {
+            rules.board_count = rules.board_count % MAX_BOARD_COUNT + 1;
+        }
+        // Two players, then three in each possible turn order
+        MenuOption::Players => {
+            let index = TURN_ORDERS
+                .iter()
+                .position(|&order| order == rules.turn_order);
+            match index {
+                _ if !rules.three_players => {
+                    rules.three_players = true;
+                    rules.turn_order = TURN_ORDERS[0];
+                }
+                Some(i) if i + 1 < TURN_ORDERS.len() => rules.turn_order = TURN_ORDERS[i + 1],
+                _ => rules.three_players = false,
+            }
+        }
+    }
+}
+
 pub fn cleanup_menu(mut commands: Commands, query: Query<Entity, With<MenuUI>>) {
     for entity in &query {
         commands.entity(entity).despawn();
@@ -1,17 +1,35 @@
 pub mod ai;
+pub mod analysis;
 pub mod camera;
+pub mod coach;
+pub mod fog;
 pub mod game;
 pub mod game_over;
+pub mod hint;
 pub mod logo;
 pub mod menu;
+pub mod numerical;
+pub mod quantum;
 pub mod rendering;
+pub mod review;
 pub mod scoreboard;
+pub mod setup;
+pub mod simultaneous;
 
 pub use ai::*;
+pub use analysis::*;
 pub use camera::*;
+pub use coach::*;
+pub use fog::*;
 pub use game::*;
 pub use game_over::*;
+pub use hint::*;
 pub use logo::*;
 pub use menu::*;
+pub use numerical::*;
+pub use quantum::*;
 pub use rendering::*;
+pub use review::*;
 pub use scoreboard::*;
+pub use setup::*;
+pub use simultaneous::*;
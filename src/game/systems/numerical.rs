@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::game::components::*;
+use crate::game::state::*;
+
+type NumberButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static NumberButton,
+        &'static mut BackgroundColor,
+    ),
+    (Changed<Interaction>, With<Button>),
+>;
+
+/// The number a human places next: the picked one while it is still
+/// available, otherwise the smallest one left.
+pub fn chosen_number(state: &GameState, selection: &MoveSelection) -> Option<u8> {
+    let remaining = state.remaining_numbers(state.turn);
+    selection
+        .number
+        .filter(|n| remaining.contains(n))
+        .or(remaining.first().copied())
+}
+
+pub fn show_number_picker(
+    mut commands: Commands,
+    state: Res<GameState>,
+    selection: Res<MoveSelection>,
+    player_config: Res<PlayerConfig>,
+    existing: Query<Entity, With<NumberPicker>>,
+) {
+    if !state.is_changed() && !selection.is_changed() {
+        return;
+    }
+
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    let current_player_type = player_config.type_of(state.turn);
+    if state.rules.mode != GameMode::Numerical
+        || state.is_over()
+        || current_player_type != PlayerType::Human
+    {
+        return;
+    }
+
+    let chosen = chosen_number(&state, &selection);
+    let opponent = state.turn.other();
+    let opponent_numbers: Vec<String> = state
+        .remaining_numbers(opponent)
+        .iter()
+        .map(u8::to_string)
+        .collect();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            NumberPicker,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!(
+                    "Player {:?}: pick a number, then a cell ({:?} has {})",
+                    state.turn,
+                    opponent,
+                    opponent_numbers.join(" ")
+                )),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(5.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for number in state.remaining_numbers(state.turn) {
+                        let color = if chosen == Some(number) {
+                            WIN_COLOR
+                        } else {
+                            UI_ACCENT
+                        };
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(44.0),
+                                height: Val::Px(40.0),
+                                margin: UiRect::horizontal(Val::Px(4.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(UI_BG),
+                            NumberButton(number),
+                        ))
+                        .with_children(|p| {
+                            p.spawn((
+                                Text::new(number.to_string()),
+                                TextFont {
+                                    font_size: 20.0,
+                                    ..default()
+                                },
+                                TextColor(color),
+                            ));
+                        });
+                    }
+                });
+        });
+}
+
+pub fn handle_number_buttons(
+    mut interaction_query: NumberButtonQuery,
+    mut selection: ResMut<MoveSelection>,
+) {
+    for (interaction, number_button, mut bg_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                selection.number = Some(number_button.0);
+            }
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::game::components::*;
+use crate::game::state::*;
+
+use super::ai::make_move;
+
+type CollapseButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static CollapseButton,
+        &'static mut BackgroundColor,
+    ),
+    (Changed<Interaction>, With<Button>),
+>;
+
+pub fn show_collapse_prompt(
+    mut commands: Commands,
+    state: Res<GameState>,
+    player_config: Res<PlayerConfig>,
+    existing: Query<Entity, With<CollapsePrompt>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(pending) = state.pending_collapse else {
+        return;
+    };
+    let chooser_type = player_config.type_of(state.turn);
+    if state.is_over() || chooser_type != PlayerType::Human {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            CollapsePrompt,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!(
+                    "Player {:?}: choose where {:?}{} collapses",
+                    state.turn,
+                    pending.mark.player,
+                    pending.mark.move_number + 1
+                )),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(WIN_COLOR),
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(5.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for cell in pending.cells {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(160.0),
+                                height: Val::Px(40.0),
+                                margin: UiRect::horizontal(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(UI_BG),
+                            CollapseButton(cell),
+                        ))
+                        .with_children(|p| {
+                            p.spawn((
+                                Text::new(cell_name(cell)),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                TextColor(UI_ACCENT),
+                            ));
+                        });
+                    }
+                });
+        });
+}
+
+pub fn handle_collapse_buttons(
+    mut interaction_query: CollapseButtonQuery,
+    mut state: ResMut<GameState>,
+) {
+    for (interaction, collapse_button, mut bg_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                let mv = GameMove::Collapse(collapse_button.0);
+                if state.legal_moves().contains(&mv) {
+                    make_move(&mut state, mv);
+                }
+            }
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+}
+
+/// Human-readable name of a cell on the 3x3 quantum board.
+fn cell_name((row, col): (usize, usize)) -> String {
+    if (row, col) == (1, 1) {
+        return "Center".to_string();
+    }
+    let vertical = ["Bottom", "Middle", "Top"][row];
+    let horizontal = ["Left", "Center", "Right"][col];
+    format!("{vertical} {horizontal}")
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
 use crate::game::components::*;
+use crate::game::fog;
+use crate::game::notakto;
+use crate::game::quantum::SpookyMark;
 use crate::game::state::*;
 use crate::game::utils::*;
 
@@ -8,25 +11,167 @@ pub fn draw_marks(
     mut commands: Commands,
     marks: Query<Entity, With<Mark>>,
     state: Res<GameState>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    review: Res<GameReview>,
+    player_config: Res<PlayerConfig>,
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<ColorMaterial>>),
+    mut animated: Local<Option<(u64, usize)>>,
 ) {
-    if !state.is_changed() {
+    if !state.is_changed() && !review.is_changed() {
         return;
     }
     for e in marks.iter() {
         commands.entity(e).despawn();
     }
+    let state = review.shown(&state);
 
-    for row in 0..BOARD_SIZE {
-        for col in 0..BOARD_SIZE {
-            if let Some(player) = state.board[row][col] {
-                let center = cell_center(row, col);
-                match player {
-                    Player::X => spawn_x(&mut commands, center, &mut meshes, &mut materials),
-                    Player::O => spawn_o(&mut commands, center, &mut meshes, &mut materials),
-                }
+    // Notakto boards and Qubic layers; dead Notakto boards are greyed out
+    if state.rules.mode.uses_boards() {
+        for (board, layout) in state.boards.iter().zip(board_layouts(state)) {
+            let dead = state.rules.mode == GameMode::Notakto && notakto::is_dead(board);
+            let alpha = if dead { FADED_ALPHA } else { 1.0 };
+            for (pos, cell_value) in board.iter() {
+                let center = layout.cell_center(pos.0, pos.1);
+                let cell = layout.cell_size();
+                match cell_value {
+                    Some(Symbol::O) => spawn_o(
+                        &mut commands,
+                        center,
+                        cell,
+                        alpha,
+                        &mut meshes,
+                        &mut materials,
+                    ),
+                    Some(_) => spawn_x(
+                        &mut commands,
+                        center,
+                        cell,
+                        alpha,
+                        &mut meshes,
+                        &mut materials,
+                    ),
+                    None => continue,
+                };
             }
+            if dead {
+                commands.spawn((
+                    Mark,
+                    Sprite::from_color(DEAD_BOARD_COLOR, Vec2::splat(layout.board_px)),
+                    Transform::from_translation(layout.center.extend(0.7)),
+                ));
+            }
+        }
+        return;
+    }
+
+    // Marks that disappear on their owner's next move are drawn faded
+    let vanishing: Vec<_> = state
+        .players()
+        .into_iter()
+        .map(|player| state.next_to_vanish(player))
+        .collect();
+
+    // Fog of war: marks hidden from the viewer stay off the board until the
+    // game ends, then grow into view one after another
+    let viewer = fog::viewer(state, &player_config);
+    if fog::curtain_shown(state, &player_config) {
+        return;
+    }
+    let mut reveal_delay = 0.0;
+
+    // Marks are redrawn on every change (hints, coach holds, the curtain),
+    // but only a new move should drop in
+    let key = (state.generation, state.moves);
+    let new_move = *animated != Some(key);
+    *animated = Some(key);
+
+    let layout = main_layout(state);
+    let cell = layout.cell_size();
+    for (pos, cell_value) in state.board.iter() {
+        let Some(symbol) = *cell_value else {
+            continue;
+        };
+        let hidden = !fog::is_visible(state, viewer, pos);
+        if hidden && !state.is_over() {
+            continue;
+        }
+        let center = layout.cell_center(pos.0, pos.1);
+        let alpha = if vanishing.contains(&Some(pos)) {
+            FADED_ALPHA
+        } else {
+            1.0
+        };
+        let parts = match symbol {
+            Symbol::X => spawn_x(
+                &mut commands,
+                center,
+                cell,
+                alpha,
+                &mut meshes,
+                &mut materials,
+            ),
+            Symbol::O => spawn_o(
+                &mut commands,
+                center,
+                cell,
+                alpha,
+                &mut meshes,
+                &mut materials,
+            ),
+            Symbol::Triangle => spawn_triangle(
+                &mut commands,
+                center,
+                cell,
+                alpha,
+                &mut meshes,
+                &mut materials,
+            ),
+            Symbol::Number(n) => vec![spawn_number(&mut commands, center, cell, n)],
+        };
+
+        if hidden {
+            for &e in &parts {
+                commands.entity(e).insert(Revealing {
+                    delay: reveal_delay,
+                });
+            }
+            reveal_delay += REVEAL_STAGGER;
+        }
+
+        // In gravity mode the newest mark falls in from above the board
+        if new_move && state.rules.gravity && state.last_move == Some(pos) {
+            let height = BOARD_PX / 2.0 + cell - center.y;
+            for e in parts {
+                commands.entity(e).insert(Falling {
+                    target_y: center.y,
+                    offset: height,
+                    velocity: 0.0,
+                });
+            }
+        }
+    }
+
+    // Obstacles nobody can claim
+    for (pos, _) in state.blocked.iter().filter(|(_, blocked)| **blocked) {
+        let center = layout.cell_center(pos.0, pos.1);
+        let tile = if layout.hex {
+            meshes.add(RegularPolygon::new(layout.hex_radius() - LINE_THICKNESS, 6))
+        } else {
+            meshes.add(Rectangle::from_length(cell - LINE_THICKNESS * 2.0))
+        };
+        commands.spawn((
+            Mark,
+            Mesh2d(tile),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(BLOCKED_COLOR))),
+            Transform::from_translation(center.extend(0.3)),
+        ));
+    }
+
+    // Quantum mode: spooky marks fill a 3x3 sub-grid of their cell
+    for (pos, spooky) in state.spooky.iter() {
+        let center = layout.cell_center(pos.0, pos.1);
+        for (i, mark) in spooky.iter().enumerate() {
+            let slot = Vec2::new((i % 3) as f32 - 1.0, 1.0 - (i / 3) as f32);
+            spawn_spooky(&mut commands, center + slot * cell * 0.28, cell, mark);
         }
     }
 }
@@ -35,18 +180,48 @@ pub fn draw_win_highlight(
     mut commands: Commands,
     mut existing: Query<(Entity, &mut Sprite), With<WinHighlight>>,
     state: Res<GameState>,
+    review: Res<GameReview>,
 ) {
     for (e, _) in existing.iter_mut() {
         commands.entity(e).despawn();
     }
+    let state = review.shown(&state);
+
+    let Some(line) = &state.winning_line else {
+        return;
+    };
+
+    // Layered boards: the line can cross layers, so highlight each cell
+    if state.rules.mode.uses_boards() {
+        let layouts = board_layouts(state);
+        for &(start, end) in &line.segments {
+            let (start, end) = (start.as_ivec3(), end.as_ivec3());
+            let steps = (end - start).abs().max_element();
+            for i in 0..=steps {
+                let cell = start + line.step * i;
+                let layout = layouts[cell.z as usize];
+                let center = layout.cell_center(cell.y as usize, cell.x as usize);
+                commands.spawn((
+                    Sprite::from_color(WIN_GLOW, Vec2::splat(layout.cell_size() - LINE_THICKNESS)),
+                    Transform::from_translation(center.extend(0.9)),
+                    WinHighlight,
+                ));
+            }
+        }
+        return;
+    }
 
-    if let Some(line) = state.winning_line {
-        let (start, end) = (line.start, line.end);
-        let start_center = cell_center(start.y as usize, start.x as usize);
-        let end_center = cell_center(end.y as usize, end.x as usize);
-        let dir = end_center - start_center;
-        let length = dir.length() + CELL_SIZE * 0.6;
-        let angle = dir.y.atan2(dir.x);
+    // One stroke per segment; a line wrapping around the edges has a piece
+    // on each side, all drawn along the line's direction
+    let layout = main_layout(state);
+    let step = line.step;
+    let dir =
+        layout.cell_center((1 + step.y) as usize, (1 + step.x) as usize) - layout.cell_center(1, 1);
+    let angle = dir.y.atan2(dir.x);
+    for &(start, end) in &line.segments {
+        let start_center = layout.cell_center(start.y as usize, start.x as usize);
+        let end_center = layout.cell_center(end.y as usize, end.x as usize);
+        let length = start_center.distance(end_center) + layout.cell_size() * 0.6;
         let mid = (start_center + end_center) * 0.5;
 
         // Glow layer (underneath)
@@ -70,111 +245,303 @@ pub fn draw_win_highlight(
     }
 }
 
+fn faded(color: Color, alpha: f32) -> Color {
+    color.with_alpha(color.alpha() * alpha)
+}
+
+pub fn draw_move_hints(
+    mut commands: Commands,
+    existing: Query<Entity, With<MoveHint>>,
+    state: Res<GameState>,
+    selection: Res<MoveSelection>,
+) {
+    if !state.is_changed() && !selection.is_changed() {
+        return;
+    }
+    for e in existing.iter() {
+        commands.entity(e).despawn();
+    }
+    if state.is_over() {
+        return;
+    }
+
+    let layout = main_layout(&state);
+    let cell = layout.cell_size();
+
+    // Quantum mode: cells a pending cycle may collapse into, or the first
+    // half of a spooky pair being placed
+    if state.rules.mode == GameMode::Quantum {
+        if let Some(pending) = state.pending_collapse {
+            for pos in pending.cells {
+                spawn_cell_hint(
+                    &mut commands,
+                    layout,
+                    pos,
+                    WIN_GLOW,
+                    cell - LINE_THICKNESS * 2.0,
+                );
+            }
+        } else if let Some(first) = selection.selected {
+            spawn_cell_hint(
+                &mut commands,
+                layout,
+                first,
+                WIN_GLOW,
+                cell - LINE_THICKNESS * 2.0,
+            );
+        }
+        return;
+    }
+
+    let Some(from) = selection.selected else {
+        return;
+    };
+    if !state.in_movement_phase() || state.board[from] != Some(state.turn.symbol()) {
+        return;
+    }
+
+    // Selected mark
+    spawn_cell_hint(
+        &mut commands,
+        layout,
+        from,
+        WIN_GLOW,
+        cell - LINE_THICKNESS * 2.0,
+    );
+
+    // Legal destinations
+    for to in state.slide_targets(from) {
+        spawn_cell_hint(&mut commands, layout, to, UI_BORDER, cell * 0.3);
+    }
+}
+
+fn spawn_cell_hint(
+    commands: &mut Commands,
+    layout: BoardLayout,
+    (row, col): (usize, usize),
+    color: Color,
+    side: f32,
+) {
+    let center = layout.cell_center(row, col);
+    commands.spawn((
+        Sprite::from_color(color, Vec2::splat(side)),
+        Transform::from_translation(Vec3::new(center.x, center.y, 0.2)),
+        MoveHint,
+    ));
+}
+
+pub fn animate_falling(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Falling)>,
+) {
+    for (e, mut transform, mut falling) in query.iter_mut() {
+        falling.velocity += FALL_ACCELERATION * time.delta_secs();
+        falling.offset = (falling.offset - falling.velocity * time.delta_secs()).max(0.0);
+        transform.translation.y = falling.target_y + falling.offset;
+
+        if falling.offset == 0.0 {
+            commands.entity(e).remove::<Falling>();
+        }
+    }
+}
+
+/// Grows hidden marks into view at the end of a fog-of-war game.
+pub fn animate_reveal(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Revealing)>,
+) {
+    for (e, mut transform, mut revealing) in query.iter_mut() {
+        revealing.delay -= time.delta_secs();
+        let progress = (-revealing.delay / REVEAL_DURATION).clamp(0.0, 1.0);
+        transform.scale = Vec3::splat(progress);
+
+        if progress >= 1.0 {
+            commands.entity(e).remove::<Revealing>();
+        }
+    }
+}
+
+/// Spawns a small spooky mark with its move number as a subscript.
+fn spawn_spooky(commands: &mut Commands, pos: Vec2, cell: f32, mark: &SpookyMark) {
+    let letter = mark.player.letter();
+    let color = mark.player.color();
+    let z = 0.5;
+    commands.spawn((
+        Mark,
+        Text2d::new(letter),
+        TextFont {
+            font_size: cell * 0.18,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(Vec3::new(pos.x - cell * 0.03, pos.y, z)),
+    ));
+    commands.spawn((
+        Mark,
+        Text2d::new((mark.move_number + 1).to_string()),
+        TextFont {
+            font_size: cell * 0.11,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_translation(Vec3::new(pos.x + cell * 0.05, pos.y - cell * 0.04, z)),
+    ));
+}
+
+/// Spawns a number mark, coloured for the player whose parity it is.
+fn spawn_number(commands: &mut Commands, center: Vec2, cell: f32, number: u8) -> Entity {
+    let color = Symbol::Number(number).owner().color();
+    commands
+        .spawn((
+            Mark,
+            Text2d::new(number.to_string()),
+            TextFont {
+                font_size: cell * 0.55,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_translation(Vec3::new(center.x, center.y, 0.5)),
+        ))
+        .id()
+}
+
+/// Stroke width of X and O marks, thinner on small cells such as Qubic layers.
+fn mark_thickness(cell: f32) -> f32 {
+    (LINE_THICKNESS * 1.5).min(cell * 0.15)
+}
+
+/// Spawns the parts of an X mark and returns their entities.
 fn spawn_x(
     commands: &mut Commands,
     center: Vec2,
+    cell: f32,
+    alpha: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
-) {
-    let len = CELL_SIZE * 0.6;
-    let thickness = LINE_THICKNESS * 1.5;
+) -> Vec<Entity> {
+    let mut parts = Vec::new();
+    let len = cell * 0.6;
+    let thickness = mark_thickness(cell);
     let z = 0.5;
     for angle in [45f32.to_radians(), -45f32.to_radians()] {
         // Glow layer (underneath)
-        commands.spawn((
-            Mark,
-            Mesh2d(meshes.add(Rectangle::new(len + 8.0, thickness + 8.0))),
-            MeshMaterial2d(materials.add(ColorMaterial::from_color(X_GLOW))),
-            Transform::from_translation(Vec3::new(center.x, center.y, z - 0.1))
-                .with_rotation(Quat::from_rotation_z(angle)),
-        ));
+        let glow = commands
+            .spawn((
+                Mark,
+                Mesh2d(meshes.add(Rectangle::new(len + 8.0, thickness + 8.0))),
+                MeshMaterial2d(materials.add(ColorMaterial::from_color(faded(X_GLOW, alpha)))),
+                Transform::from_translation(Vec3::new(center.x, center.y, z - 0.1))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ))
+            .id();
+        parts.push(glow);
 
         // Main X line (on top)
-        commands.spawn((
-            Mark,
-            Mesh2d(meshes.add(Rectangle::new(len, thickness))),
-            MeshMaterial2d(materials.add(ColorMaterial::from_color(X_COLOR))),
-            Transform::from_translation(Vec3::new(center.x, center.y, z))
-                .with_rotation(Quat::from_rotation_z(angle)),
-        ));
+        let line = commands
+            .spawn((
+                Mark,
+                Mesh2d(meshes.add(Rectangle::new(len, thickness))),
+                MeshMaterial2d(materials.add(ColorMaterial::from_color(faded(X_COLOR, alpha)))),
+                Transform::from_translation(Vec3::new(center.x, center.y, z))
+                    .with_rotation(Quat::from_rotation_z(angle)),
+            ))
+            .id();
+        parts.push(line);
     }
+    parts
 }
 
+/// Spawns the parts of an O mark and returns their entities.
 fn spawn_o(
     commands: &mut Commands,
     center: Vec2,
+    cell: f32,
+    alpha: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
-) {
-    let radius = CELL_SIZE * 0.3;
-    let thickness = LINE_THICKNESS * 1.5;
+) -> Vec<Entity> {
+    let radius = cell * 0.3;
+    let thickness = mark_thickness(cell);
     let z = 0.5;
 
     // Glow layer (underneath)
-    commands.spawn((
-        Mark,
-        Mesh2d(meshes.add(Circle::new(radius + 4.0))),
-        MeshMaterial2d(materials.add(ColorMaterial::from_color(O_GLOW))),
-        Transform::from_translation(Vec3::new(center.x, center.y, z - 0.1)),
-    ));
+    let glow = commands
+        .spawn((
+            Mark,
+            Mesh2d(meshes.add(Circle::new(radius + 4.0))),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(faded(O_GLOW, alpha)))),
+            Transform::from_translation(Vec3::new(center.x, center.y, z - 0.1)),
+        ))
+        .id();
 
     // Outer circle
-    commands.spawn((
-        Mark,
-        Mesh2d(meshes.add(Circle::new(radius))),
-        MeshMaterial2d(materials.add(ColorMaterial::from_color(O_COLOR))),
-        Transform::from_translation(Vec3::new(center.x, center.y, z)),
-    ));
+    let outer = commands
+        .spawn((
+            Mark,
+            Mesh2d(meshes.add(Circle::new(radius))),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(faded(O_COLOR, alpha)))),
+            Transform::from_translation(Vec3::new(center.x, center.y, z)),
+        ))
+        .id();
 
     // Inner circle (background color to create ring effect)
-    commands.spawn((
-        Mark,
-        Mesh2d(meshes.add(Circle::new(radius - thickness))),
-        MeshMaterial2d(materials.add(ColorMaterial::from_color(BG_COLOR))),
-        Transform::from_translation(Vec3::new(center.x, center.y, z + 0.01)),
-    ));
+    let inner = commands
+        .spawn((
+            Mark,
+            Mesh2d(meshes.add(Circle::new(radius - thickness))),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(BG_COLOR))),
+            Transform::from_translation(Vec3::new(center.x, center.y, z + 0.01)),
+        ))
+        .id();
+    vec![glow, outer, inner]
 }
 
-pub fn check_winner(board: &[[Option<Player>; BOARD_SIZE]; BOARD_SIZE]) -> Option<WinningLine> {
-    // rows
-    for (r, row) in board.iter().enumerate() {
-        if let Some(p) = row[0] {
-            if row.iter().skip(1).all(|&cell| cell == Some(p)) {
-                return Some(WinningLine {
-                    start: UVec2::new(0, r as u32),
-                    end: UVec2::new((BOARD_SIZE - 1) as u32, r as u32),
-                });
-            }
-        }
-    }
-    // cols
-    for c in 0..BOARD_SIZE {
-        if let Some(p) = board[0][c] {
-            if board.iter().skip(1).all(|row| row[c] == Some(p)) {
-                return Some(WinningLine {
-                    start: UVec2::new(c as u32, 0),
-                    end: UVec2::new(c as u32, (BOARD_SIZE - 1) as u32),
-                });
-            }
-        }
-    }
-    // diag \
-    if let Some(p) = board[0][0] {
-        if (1..BOARD_SIZE).all(|i| board[i][i] == Some(p)) {
-            return Some(WinningLine {
-                start: UVec2::new(0, 0),
-                end: UVec2::new((BOARD_SIZE - 1) as u32, (BOARD_SIZE - 1) as u32),
-            });
-        }
-    }
-    // diag /
-    if let Some(p) = board[0][BOARD_SIZE - 1] {
-        if (1..BOARD_SIZE).all(|i| board[i][BOARD_SIZE - 1 - i] == Some(p)) {
-            return Some(WinningLine {
-                start: UVec2::new((BOARD_SIZE - 1) as u32, 0),
-                end: UVec2::new(0, (BOARD_SIZE - 1) as u32),
-            });
-        }
-    }
-    None
+/// Spawns the third player's mark: a triangular ring drawn like the O.
+fn spawn_triangle(
+    commands: &mut Commands,
+    center: Vec2,
+    cell: f32,
+    alpha: f32,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+) -> Vec<Entity> {
+    let radius = cell * 0.34;
+    let thickness = mark_thickness(cell);
+    let z = 0.5;
+    // The triangle sits a little low so it looks centred in the cell
+    let origin = Vec3::new(center.x, center.y - radius * 0.15, z);
+
+    // Glow layer (underneath)
+    let glow = commands
+        .spawn((
+            Mark,
+            Mesh2d(meshes.add(RegularPolygon::new(radius + 8.0, 3))),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(faded(TRIANGLE_GLOW, alpha)))),
+            Transform::from_translation(origin - Vec3::Z * 0.1),
+        ))
+        .id();
+
+    // Outer triangle
+    let outer = commands
+        .spawn((
+            Mark,
+            Mesh2d(meshes.add(RegularPolygon::new(radius, 3))),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(faded(TRIANGLE_COLOR, alpha)))),
+            Transform::from_translation(origin),
+        ))
+        .id();
+
+    // Inner triangle (background color to create the outline); a triangle's
+    // sides sit at half its radius, so the ring is `thickness` wide
+    let inner = commands
+        .spawn((
+            Mark,
+            Mesh2d(meshes.add(RegularPolygon::new(radius - thickness * 2.0, 3))),
+            MeshMaterial2d(materials.add(ColorMaterial::from_color(BG_COLOR))),
+            Transform::from_translation(origin + Vec3::Z * 0.01),
+        ))
+        .id();
+    vec![glow, outer, inner]
 }
@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::game::components::*;
+use crate::game::review::{self, Verdict};
+use crate::game::state::*;
+use crate::game::systems::hint_cells;
+
+type AnalyseButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<AnalyseButton>),
+>;
+
+type ReviewButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static ReviewButton,
+        &'static mut BackgroundColor,
+    ),
+    (Changed<Interaction>, Without<AnalyseButton>),
+>;
+
+type ReviewEntitiesQuery<'w, 's> =
+    Query<'w, 's, Entity, Or<(With<ReviewPanel>, With<ReviewMarker>)>>;
+
+/// Starts the review from the game over screen, and steps through the moves
+/// with the panel buttons or the arrow keys.
+pub fn handle_review_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut analyse_buttons: AnalyseButtonQuery,
+    mut review_buttons: ReviewButtonQuery,
+    state: Res<GameState>,
+    mut game_review: ResMut<GameReview>,
+) {
+    for (interaction, mut bg_color) in &mut analyse_buttons {
+        match *interaction {
+            Interaction::Pressed => start_review(&mut game_review, &state),
+            Interaction::Hovered => *bg_color = BackgroundColor(UI_BORDER),
+            Interaction::None => *bg_color = BackgroundColor(UI_BG),
+        }
+    }
+    if !game_review.active {
+        return;
+    }
+
+    let mut action = None;
+    for (interaction, button, mut bg_color) in &mut review_buttons {
+        match *interaction {
+            Interaction::Pressed => action = Some(*button),
+            Interaction::Hovered => *bg_color = BackgroundColor(UI_BORDER),
+            Interaction::None => *bg_color = BackgroundColor(UI_BG),
+        }
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        action = Some(ReviewButton::Previous);
+    } else if keys.just_pressed(KeyCode::ArrowRight) {
+        action = Some(ReviewButton::Next);
+    }
+
+    match action {
+        Some(ReviewButton::Previous) if game_review.step > 0 => {
+            let step = game_review.step - 1;
+            show_step(&mut game_review, &state, step);
+        }
+        Some(ReviewButton::Next) if game_review.step < state.history.len() => {
+            let step = game_review.step + 1;
+            show_step(&mut game_review, &state, step);
+        }
+        Some(ReviewButton::Close) => game_review.close(),
+        _ => {}
+    }
+}
+
+fn start_review(game_review: &mut GameReview, state: &GameState) {
+    game_review.close();
+    let snapshot = state.clone();
+    let cancel = Arc::new(AtomicBool::new(false));
+    game_review.cancel = cancel.clone();
+    game_review.task =
+        Some(AsyncComputeTaskPool::get().spawn(async move { review::review(&snapshot, &cancel) }));
+    game_review.active = true;
+    show_step(game_review, state, state.history.len());
+}
+
+fn show_step(game_review: &mut GameReview, state: &GameState, step: usize) {
+    game_review.step = step;
+    game_review.position = review::replay(state, step);
+}
+
+/// Collects the grades and redraws the review panel, hiding the game over
+/// screen while the review is open.
+pub fn update_review(
+    mut commands: Commands,
+    state: Res<GameState>,
+    mut game_review: ResMut<GameReview>,
+    panels: ReviewEntitiesQuery,
+    mut game_over: Query<&mut Node, With<GameOverUI>>,
+) {
+    // Polling must not count as a change, or the board would redraw every frame
+    if let Some(task) = game_review.bypass_change_detection().task.as_mut() {
+        if let Some(moves) = block_on(poll_once(task)) {
+            game_review.task = None;
+            game_review.moves = moves;
+        }
+    }
+    // A new game ends the review
+    if game_review.active && !state.is_over() {
+        game_review.close();
+    }
+    if !game_review.is_changed() && !state.is_changed() {
+        return;
+    }
+
+    for e in panels.iter() {
+        commands.entity(e).despawn();
+    }
+    for mut node in game_over.iter_mut() {
+        node.display = if game_review.active {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+    if !game_review.active {
+        return;
+    }
+
+    // The move that led to the board on show
+    let graded = game_review
+        .step
+        .checked_sub(1)
+        .and_then(|i| game_review.moves.get(i));
+    // The opening keeps the seed the game was played with, so the review
+    // tells how to play it again
+    let seed = state
+        .opening
+        .as_ref()
+        .map_or(state.seed, |opening| opening.seed);
+    let mut heading = format!(
+        "Review: move {} of {}, seed {seed}",
+        game_review.step,
+        state.history.len()
+    );
+    let before = game_review
+        .step
+        .checked_sub(1)
+        .and_then(|index| Some((state.history[index], review::replay(&state, index)?)));
+    if let Some((mv, before)) = before {
+        let label = match graded {
+            Some(graded) => graded.label(),
+            None => "analysing...".to_string(),
+        };
+        heading.push_str(&format!(
+            "\n{} {}: {label}",
+            before.turn.letter(),
+            review::describe_move(mv)
+        ));
+        let index = game_review.step - 1;
+        if let Some(hint) = state
+            .hints
+            .iter()
+            .find(|hint| hint.ply == index && hint.player == before.turn)
+        {
+            heading.push_str(&format!(" (hint: {})", review::describe_move(hint.mv)));
+        }
+
+        // Highlight the move, coloured by its grade
+        let color = match graded.and_then(|graded| graded.verdict()) {
+            Some(Verdict::Best) => ANALYSIS_WIN,
+            Some(Verdict::Inaccuracy) => ANALYSIS_DRAW,
+            Some(Verdict::Blunder) => ANALYSIS_LOSS,
+            None => UI_BORDER,
+        };
+        for (layout, (row, col)) in hint_cells(&before, mv) {
+            let center = layout.cell_center(row, col);
+            commands.spawn((
+                Sprite::from_color(
+                    color.with_alpha(0.35),
+                    Vec2::splat(layout.cell_size() - LINE_THICKNESS * 2.0),
+                ),
+                Transform::from_translation(Vec3::new(center.x, center.y, 0.15)),
+                ReviewMarker,
+            ));
+        }
+    }
+
+    let accuracy: Vec<String> = state
+        .players()
+        .into_iter()
+        .filter_map(|player| {
+            review::accuracy(&game_review.moves, player)
+                .map(|percent| format!("{} {percent}%", player.letter()))
+        })
+        .collect();
+    let accuracy = if accuracy.is_empty() {
+        String::new()
+    } else {
+        format!("Accuracy: {}", accuracy.join(" | "))
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(UI_BG),
+            ReviewPanel,
+        ))
+        .with_children(|parent| {
+            for (text, size) in [(heading, 18.0), (accuracy, 16.0)] {
+                parent.spawn((
+                    Text::new(text),
+                    TextFont {
+                        font_size: size,
+                        ..default()
+                    },
+                    TextColor(UI_ACCENT),
+                    TextLayout::new_with_justify(Justify::Center),
+                ));
+            }
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for (button, label) in [
+                        (ReviewButton::Previous, "< Prev"),
+                        (ReviewButton::Next, "Next >"),
+                        (ReviewButton::Close, "Done"),
+                    ] {
+                        row.spawn((
+                            Button,
+                            Node {
+                                width: Val::Px(110.0),
+                                height: Val::Px(36.0),
+                                margin: UiRect::horizontal(Val::Px(5.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(UI_BG),
+                            button,
+                        ))
+                        .with_children(|p| {
+                            p.spawn((
+                                Text::new(label),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                TextColor(UI_ACCENT),
+                            ));
+                        });
+                    }
+                });
+        });
+}
@@ -7,6 +7,7 @@ pub fn spawn_scoreboard(
     mut commands: Commands,
     player_config: Res<PlayerConfig>,
     score: Res<Score>,
+    rules: Res<GameRules>,
 ) {
     commands
         .spawn((
@@ -42,8 +43,25 @@ pub fn spawn_scoreboard(
                     ..default()
                 },
             ));
+            if rules.normalized().three_players {
+                parent.spawn((
+                    Text::new(format!(
+                        "Player Triangle: {}",
+                        player_config.triangle_type.label()
+                    )),
+                    TextFont {
+                        font_size: 18.0,
+                        ..default()
+                    },
+                    TextColor(TRIANGLE_COLOR),
+                    Node {
+                        margin: UiRect::top(Val::Px(5.0)),
+                        ..default()
+                    },
+                ));
+            }
             parent.spawn((
-                Text::new(""),
+                Text::new(rules_summary(&rules.normalized())),
                 TextFont {
                     font_size: 14.0,
                     ..default()
@@ -55,10 +73,16 @@ pub fn spawn_scoreboard(
                 },
             ));
             parent.spawn((
-                Text::new(format!(
-                    "X Wins: {} | O Wins: {} | Draws: {}",
-                    score.x_wins, score.o_wins, score.draws
-                )),
+                Text::new(""),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+                SeedText,
+            ));
+            parent.spawn((
+                Text::new(score_text(&score, &rules.normalized())),
                 TextFont {
                     font_size: 16.0,
                     ..default()
@@ -70,18 +94,203 @@ pub fn spawn_scoreboard(
                 },
                 ScoreText,
             ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+                Node {
+                    margin: UiRect::top(Val::Px(5.0)),
+                    ..default()
+                },
+                ThinkingText,
+            ));
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        margin: UiRect::top(Val::Px(8.0)),
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                        justify_content: JustifyContent::Center,
+                        display: Display::None,
+                        ..default()
+                    },
+                    BackgroundColor(UI_BG),
+                    HintButton,
+                ))
+                .with_children(|button| {
+                    button.spawn((
+                        Text::new("Hint (H)"),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(UI_ACCENT),
+                        HintButtonText,
+                    ));
+                });
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(HINT_COLOR.with_alpha(1.0)),
+                Node {
+                    margin: UiRect::top(Val::Px(5.0)),
+                    ..default()
+                },
+                HintText,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(UI_ACCENT),
+                Node {
+                    margin: UiRect::top(Val::Px(5.0)),
+                    ..default()
+                },
+                AnalysisText,
+            ));
         });
 }
 
-pub fn update_scoreboard(score: Res<Score>, mut query: Query<&mut Text, With<ScoreText>>) {
+/// Shows which computer is working out a move, with animated dots.
+pub fn update_thinking_indicator(
+    time: Res<Time>,
+    thinking: Res<ComputerThinking>,
+    mut query: Query<&mut Text, With<ThinkingText>>,
+) {
+    let text = match &thinking.pending {
+        Some(pending) => {
+            let dots = (time.elapsed_secs() * 3.0) as usize % 4;
+            format!("Computer {} is thinking{}", pending.name, ".".repeat(dots))
+        }
+        None => String::new(),
+    };
+    for mut label in query.iter_mut() {
+        if **label != text {
+            **label = text.clone();
+        }
+    }
+}
+
+pub fn update_seed_text(state: Res<GameState>, mut query: Query<&mut Text, With<SeedText>>) {
+    let text = format!("Seed: {}", state.seed);
+    for mut label in query.iter_mut() {
+        if **label != text {
+            **label = text.clone();
+        }
+    }
+}
+
+pub fn update_scoreboard(
+    score: Res<Score>,
+    rules: Res<GameRules>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+) {
     if !score.is_changed() {
         return;
     }
 
     for mut text in query.iter_mut() {
-        **text = format!(
-            "X Wins: {} | O Wins: {} | Draws: {}",
-            score.x_wins, score.o_wins, score.draws
+        **text = score_text(&score, &rules.normalized());
+    }
+}
+
+fn score_text(score: &Score, rules: &GameRules) -> String {
+    let mut text = format!("X Wins: {} | O Wins: {}", score.x_wins, score.o_wins);
+    if rules.three_players || score.triangle_wins > 0 {
+        text.push_str(&format!(" | T Wins: {}", score.triangle_wins));
+    }
+    text.push_str(&format!(" | Draws: {}", score.draws));
+    if score.x_hints > 0 || score.o_hints > 0 || score.triangle_hints > 0 {
+        text.push_str(&format!(
+            "\nHints used: X {} | O {}",
+            score.x_hints, score.o_hints
+        ));
+        if rules.three_players || score.triangle_hints > 0 {
+            text.push_str(&format!(" | T {}", score.triangle_hints));
+        }
+    }
+    if score.coach_warnings > 0 {
+        text.push_str(&format!(
+            "\nCoach warnings: {} | Taken back: {}",
+            score.coach_warnings, score.coach_take_backs
+        ));
+    }
+    if score.x_half_points > 0 || score.o_half_points > 0 {
+        text.push_str(&format!(
+            "\nHalf points: X {} | O {}",
+            score.x_half_points, score.o_half_points
+        ));
+    }
+    text
+}
+
+fn rules_summary(rules: &GameRules) -> String {
+    if rules.mode == GameMode::Notakto {
+        let plural = if rules.board_count == 1 { "" } else { "s" };
+        return format!(
+            "Mode: {} | {} board{}",
+            rules.mode.label(),
+            rules.board_count,
+            plural
+        );
+    }
+    let mut summary = format!(
+        "Mode: {} | {}x{} | {} in a row",
+        rules.mode.label(),
+        rules.board_size,
+        rules.board_size,
+        rules.win_length
+    );
+    if rules.gravity {
+        summary.push_str(" | Gravity");
+    }
+    if rules.hex {
+        summary.push_str(" | Hex");
+    }
+    if rules.wrap {
+        summary.push_str(" | Wrap");
+    }
+    if rules.fog {
+        summary.push_str(" | Fog");
+    }
+    if rules.three_players {
+        let order: Vec<_> = rules.turn_order.iter().map(|p| p.letter()).collect();
+        summary.push_str(&format!("\nThree players, in order {}", order.join(", ")));
+    }
+    if rules.setup != Setup::None {
+        summary.push_str(&format!("\nSetup: {}", rules.setup.label()));
+    }
+    if rules.mode == GameMode::Qubic {
+        return format!(
+            "Mode: {} | {n}x{n}x{n} | {} in a row in any direction",
+            rules.mode.label(),
+            rules.win_length,
+            n = rules.board_size
         );
     }
+    if rules.mode == GameMode::Numerical {
+        return format!(
+            "Mode: {} | Lines summing to 15\nX plays odd numbers, O plays even",
+            rules.mode.label()
+        );
+    }
+    if rules.mode == GameMode::Simultaneous {
+        summary.push_str("\nPicks are secret; a shared pick blocks the cell");
+    }
+    if rules.mode == GameMode::OrderChaos {
+        summary.push_str("\nX is Order (make five), O is Chaos (stop it)");
+    }
+    if rules.mode.free_symbol_choice() {
+        summary.push_str("\nClick: X | Right/Shift+click: O");
+    }
+    summary
 }
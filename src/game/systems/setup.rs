@@ -0,0 +1,121 @@
+use bevy::prelude::*;
+
+use crate::game::components::*;
+use crate::game::setup;
+use crate::game::state::*;
+
+type StartMatchButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<StartMatchButton>),
+>;
+
+pub fn show_setup_prompt(
+    mut commands: Commands,
+    state: Res<GameState>,
+    existing: Query<Entity, With<SetupPrompt>>,
+    mut problems: Query<&mut Text, With<SetupProblemText>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    let problem =
+        setup::problem(&state).map_or(String::new(), |problem| format!("Can't start: {problem}"));
+    let shown = !existing.is_empty();
+    if shown == state.setup_phase {
+        for mut text in &mut problems {
+            **text = problem.clone();
+        }
+        return;
+    }
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+    if !state.setup_phase {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            SetupPrompt,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Setup: click cells to cycle blocked, X and O"),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(WIN_COLOR),
+            ));
+            parent.spawn((
+                Text::new(problem),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(ANALYSIS_LOSS),
+                SetupProblemText,
+            ));
+
+            parent
+                .spawn((
+                    Button,
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(40.0),
+                        margin: UiRect::top(Val::Px(5.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BackgroundColor(UI_BG),
+                    StartMatchButton,
+                ))
+                .with_children(|p| {
+                    p.spawn((
+                        Text::new("Start (Enter)"),
+                        TextFont {
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(UI_ACCENT),
+                    ));
+                });
+        });
+}
+
+/// Starts play from the setup with the button or Enter. A setup with a
+/// problem stays open; the prompt says what is wrong.
+pub fn handle_setup_buttons(
+    mut interaction_query: StartMatchButtonQuery,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<GameState>,
+) {
+    let mut start = keys.just_pressed(KeyCode::Enter);
+    for (interaction, mut bg_color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => start = true,
+            Interaction::Hovered => {
+                *bg_color = BackgroundColor(UI_BORDER);
+            }
+            Interaction::None => {
+                *bg_color = BackgroundColor(UI_BG);
+            }
+        }
+    }
+    if start && state.setup_phase {
+        // The prompt already shows why a setup can't start
+        let _ = setup::finish(&mut state);
+    }
+}
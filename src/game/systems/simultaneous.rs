@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::game::components::*;
+use crate::game::fog;
+use crate::game::state::*;
+
+/// Tells players whose secret pick is due and how the last round went.
+/// Picks already made stay off the board until the round is revealed.
+pub fn show_round_prompt(
+    mut commands: Commands,
+    state: Res<GameState>,
+    player_config: Res<PlayerConfig>,
+    existing: Query<Entity, With<RoundPrompt>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+    if state.rules.mode != GameMode::Simultaneous
+        || state.setup_phase
+        || state.is_over()
+        || fog::curtain_shown(&state, &player_config)
+    {
+        return;
+    }
+
+    let mut lines = Vec::new();
+    if state.round.last_collided() {
+        lines.push("Last round: both picked the same cell, so it is blocked".to_string());
+    }
+    for (player, _) in &state.round.picks {
+        lines.push(format!("Player {:?} has chosen", player));
+    }
+    lines.push(format!(
+        "Round {}: Player {:?}, pick a cell in secret",
+        state.moves + 1,
+        state.turn
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            RoundPrompt,
+        ))
+        .with_children(|parent| {
+            for line in lines {
+                parent.spawn((
+                    Text::new(line),
+                    TextFont {
+                        font_size: 18.0,
+                        ..default()
+                    },
+                    TextColor(WIN_COLOR),
+                ));
+            }
+        });
+}
@@ -1,22 +1,176 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
-use super::state::{BOARD_SIZE, CELL_SIZE};
+use super::state::{GameState, BOARD_PX};
 
-pub fn cell_center(row: usize, col: usize) -> Vec2 {
-    let board_px = CELL_SIZE * BOARD_SIZE as f32;
-    let half = board_px / 2.0;
-    Vec2::new(
-        col as f32 * CELL_SIZE + CELL_SIZE * 0.5 - half,
-        row as f32 * CELL_SIZE + CELL_SIZE * 0.5 - half,
-    )
+// Horizontal gap between boards drawn side by side, and the width they share
+const BOARD_GAP: f32 = 30.0;
+const BOARD_ROW_WIDTH: f32 = 560.0;
+
+// Ratio of a hexagon's width across its sides to its corner radius
+const SQRT_3: f32 = 1.732_050_8;
+
+/// Where a board is drawn in the world. Square boards are `board_px` wide;
+/// hex boards are a rhombus of pointy-top hexagons fitted into the same area.
+#[derive(Clone, Copy, Debug)]
+pub struct BoardLayout {
+    pub center: Vec2,
+    pub size: usize,
+    pub board_px: f32,
+    pub hex: bool,
+}
+
+impl BoardLayout {
+    /// The single board centred on screen.
+    pub fn main(size: usize) -> Self {
+        Self {
+            center: Vec2::ZERO,
+            size,
+            board_px: BOARD_PX,
+            hex: false,
+        }
+    }
+
+    /// A hex board centred on screen. Cell `(row, col)` is the axial
+    /// coordinate `r = row, q = col`, so each row sits half a cell further right.
+    pub fn hex(size: usize) -> Self {
+        Self {
+            hex: true,
+            ..Self::main(size)
+        }
+    }
+
+    /// Board `index` of `count` boards laid out side by side.
+    pub fn row(size: usize, count: usize, index: usize) -> Self {
+        let board_px =
+            BOARD_PX.min((BOARD_ROW_WIDTH - BOARD_GAP * (count - 1) as f32) / count as f32);
+        let offset = index as f32 - (count - 1) as f32 / 2.0;
+        Self {
+            center: Vec2::new(offset * (board_px + BOARD_GAP), 0.0),
+            size,
+            board_px,
+            hex: false,
+        }
+    }
+
+    /// Width of a cell; for hexagons the distance between opposite sides.
+    pub fn cell_size(&self) -> f32 {
+        if self.hex {
+            return self.hex_radius() * SQRT_3;
+        }
+        self.board_px / self.size as f32
+    }
+
+    /// Centre-to-corner radius of a hex cell, the largest that fits the
+    /// rhombus into `board_px` high and `BOARD_ROW_WIDTH` wide.
+    pub fn hex_radius(&self) -> f32 {
+        let n = self.size as f32;
+        let height = 1.5 * (n - 1.0) + 2.0;
+        let width = SQRT_3 * (1.5 * n - 0.5);
+        (self.board_px / height).min(BOARD_ROW_WIDTH / width)
+    }
+
+    pub fn cell_center(&self, row: usize, col: usize) -> Vec2 {
+        if self.hex {
+            return self.center + self.hex_offset(row as f32, col as f32);
+        }
+        let cell = self.cell_size();
+        let half = self.board_px / 2.0;
+        self.center
+            + Vec2::new(
+                col as f32 * cell + cell * 0.5 - half,
+                row as f32 * cell + cell * 0.5 - half,
+            )
+    }
+
+    /// Maps a world position to the `(row, col)` of the board cell under it.
+    pub fn cell_at(&self, pos: Vec2) -> Option<(usize, usize)> {
+        if self.hex {
+            return self.hex_cell_at(pos);
+        }
+        let half = self.board_px / 2.0;
+        let x = pos.x - self.center.x + half;
+        let y = pos.y - self.center.y + half;
+        if x < 0.0 || y < 0.0 || x >= self.board_px || y >= self.board_px {
+            return None;
+        }
+        let cell = self.cell_size();
+        let last = self.size - 1;
+        Some((
+            ((y / cell).floor() as usize).min(last),
+            ((x / cell).floor() as usize).min(last),
+        ))
+    }
+
+    /// Position of axial `(r, q)` relative to the board centre.
+    fn hex_offset(&self, r: f32, q: f32) -> Vec2 {
+        let radius = self.hex_radius();
+        let mid = (self.size - 1) as f32 / 2.0;
+        Vec2::new(
+            radius * SQRT_3 * (q - mid + (r - mid) / 2.0),
+            radius * 1.5 * (r - mid),
+        )
+    }
+
+    /// Inverse of `hex_offset`, rounding to the nearest hexagon in cube coordinates.
+    fn hex_cell_at(&self, pos: Vec2) -> Option<(usize, usize)> {
+        let radius = self.hex_radius();
+        let mid = (self.size - 1) as f32 / 2.0;
+        let p = pos - self.center;
+        let r = p.y / (1.5 * radius);
+        let q = p.x / (SQRT_3 * radius) - r / 2.0;
+        let (r, q) = (r + mid, q + mid);
+
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        let in_range = |v: f32| (0.0..self.size as f32).contains(&v);
+        (in_range(rr) && in_range(rq)).then_some((rr as usize, rq as usize))
+    }
+}
+
+/// Layout of the single board, square or hex depending on the rules.
+pub fn main_layout(state: &GameState) -> BoardLayout {
+    let size = state.board.size();
+    if state.rules.hex {
+        BoardLayout::hex(size)
+    } else {
+        BoardLayout::main(size)
+    }
+}
+
+/// Layouts of every board in play: one per Notakto board or Qubic layer,
+/// otherwise the main board.
+pub fn board_layouts(state: &GameState) -> Vec<BoardLayout> {
+    let size = state.board.size();
+    if state.rules.mode.uses_boards() {
+        let count = state.boards.len();
+        (0..count)
+            .map(|index| BoardLayout::row(size, count, index))
+            .collect()
+    } else {
+        vec![main_layout(state)]
+    }
+}
+
+/// Window and camera queries needed to map the cursor into the world.
+#[derive(SystemParam)]
+pub struct WorldCursor<'w, 's> {
+    windows: Query<'w, 's, &'static Window>,
+    camera_q: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
 }
 
-pub fn world_cursor_pos(
-    windows: Query<&Window>,
-    camera_q: Query<(&Camera, &GlobalTransform)>,
-) -> Option<Vec2> {
-    let window = windows.single().ok()?;
-    let (camera, cam_transform) = camera_q.single().ok()?;
-    let cursor = window.cursor_position()?;
-    camera.viewport_to_world_2d(cam_transform, cursor).ok()
+impl WorldCursor<'_, '_> {
+    pub fn position(&self) -> Option<Vec2> {
+        let window = self.windows.single().ok()?;
+        let (camera, cam_transform) = self.camera_q.single().ok()?;
+        let cursor = window.cursor_position()?;
+        camera.viewport_to_world_2d(cam_transform, cursor).ok()
+    }
 }
@@ -1,9 +1,21 @@
 use bevy::prelude::*;
 
 mod game;
-use game::GamePlugin;
+use game::{CliArgs, GamePlugin};
 
 fn main() {
+    let args = CliArgs::parse(std::env::args());
+    if args.engine {
+        if let Err(err) = game::run_reference_engine() {
+            eprintln!("Engine stopped: {err}");
+        }
+        return;
+    }
+    if let Some(games) = args.calibrate {
+        game::run_calibration(games);
+        return;
+    }
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -14,6 +26,10 @@ fn main() {
             }),
             ..default()
         }))
-        .add_plugins(GamePlugin)
+        .add_plugins(GamePlugin {
+            bots: args.bots,
+            mcts: args.mcts,
+            seed: args.seed,
+        })
         .run();
 }